@@ -1,10 +1,15 @@
+use crate::animation::RateFunc;
+
 pub struct Action {
     pub start_time: f32,
     pub duration: f32,
+    pub rate_func: Option<RateFunc>,
 
     pub on_start: Box<dyn FnMut()>,
     pub on_stop: Box<dyn FnMut()>,
     pub on_update: Box<dyn FnMut(f32, f32)>,
+    // Replaces on_update(0.0, 0.0) when rewinding, for actions whose start state isn't progress 0
+    pub on_rewind: Option<Box<dyn FnMut()>>,
 
     started: bool,
     stopped: bool,
@@ -15,17 +20,21 @@ impl Action {
         Self {
             start_time: 0.0,
             duration: 1.0,
+            rate_func: None,
             on_start: Box::new(|| {}),
             on_stop: Box::new(|| {}),
             on_update: Box::new(|_, _| {}),
+            on_rewind: None,
 
             started: false,
             stopped: false,
         }
     }
 
+    // Progress may move backwards, the action then replays from wherever it lands
     pub fn execute(&mut self, progress: f32, elapsed_time: f32) {
-        if self.stopped || progress < 0.0 {
+        if progress < 0.0 {
+            self.rewind();
             return;
         }
 
@@ -34,13 +43,10 @@ impl Action {
             self.started = true;
         }
 
-        if progress == 0.0 {
-            return;
-        }
-
         if progress > 1.0 {
             if !self.stopped {
-                (self.on_update)(1.0, self.duration);
+                let progress = self.rate(1.0);
+                (self.on_update)(progress, self.duration);
                 (self.on_stop)();
                 self.stopped = true;
             }
@@ -48,9 +54,49 @@ impl Action {
             return;
         }
 
+        // Scrubbing back from past the end
+        self.stopped = false;
+
+        let progress = self.rate(progress);
         (self.on_update)(progress, elapsed_time);
     }
 
+    // Puts back the state captured by on_start, which runs again once the action restarts
+    pub fn rewind(&mut self) {
+        if !self.started {
+            return;
+        }
+
+        match self.on_rewind.as_mut() {
+            Some(on_rewind) => on_rewind(),
+            None => (self.on_update)(0.0, 0.0),
+        }
+
+        self.started = false;
+        self.stopped = false;
+    }
+
+    // Ends a running action wherever it currently is, without jumping to its end state
+    pub fn stop(&mut self) {
+        if !self.started || self.stopped {
+            return;
+        }
+
+        (self.on_stop)();
+        self.stopped = true;
+    }
+
+    // Linear when no rate function is set
+    pub fn rate(&self, progress: f32) -> f32 {
+        self.rate_func
+            .as_ref()
+            .map_or(progress, |rate_func| rate_func(progress))
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
     pub fn is_stopped(&self) -> bool {
         self.stopped
     }
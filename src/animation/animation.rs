@@ -1,6 +1,32 @@
-use crate::{Scene, animation::Action};
+use crate::{
+    Scene,
+    animation::{Action, RateFunc},
+};
 use std::{cell::RefCell, rc::Rc};
 
 pub trait Animation {
     fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action;
+
+    fn with_rate_func<F: Fn(f32) -> f32 + 'static>(self, rate_func: F) -> WithRateFunc<Self>
+    where
+        Self: Sized,
+    {
+        WithRateFunc {
+            animation: self,
+            rate_func: Rc::new(rate_func),
+        }
+    }
+}
+
+pub struct WithRateFunc<Ani: Animation> {
+    pub animation: Ani,
+    pub rate_func: RateFunc,
+}
+
+impl<Ani: Animation> Animation for WithRateFunc<Ani> {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = self.animation.into_action(scene);
+        out.rate_func = Some(self.rate_func);
+        out
+    }
 }
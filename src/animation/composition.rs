@@ -0,0 +1,327 @@
+use crate::{
+    Scene,
+    animation::{Action, Animation},
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+type ActionFactory = Box<dyn FnOnce(Rc<RefCell<Scene>>) -> Action>;
+
+// Children are laid out in seconds. Queueing a composite for another duration stretches
+// the whole layout, while children keep reporting elapsed time in their own seconds
+struct Entry {
+    factory: ActionFactory,
+    start: f32,
+    duration: f32,
+}
+
+struct Track {
+    action: Action,
+    start: f32,
+    duration: f32,
+}
+
+#[derive(Default)]
+struct Composite {
+    entries: Vec<Entry>,
+    duration: f32,
+}
+
+impl Composite {
+    fn push<Ani: Animation + 'static>(&mut self, animation: Ani, start: f32, duration: f32) {
+        self.entries.push(Entry {
+            factory: Box::new(move |scene| animation.into_action(scene)),
+            start,
+            duration,
+        });
+        self.duration = self.duration.max(start + duration);
+    }
+
+    fn last_entry(&self) -> Option<&Entry> {
+        self.entries.last()
+    }
+
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let total = self.duration.max(f32::EPSILON);
+        let tracks: Vec<Track> = self
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let mut action = (entry.factory)(scene.clone());
+                action.duration = entry.duration;
+
+                Track {
+                    action,
+                    start: entry.start,
+                    duration: entry.duration,
+                }
+            })
+            .collect();
+
+        let mut out = Action::new();
+        let tracks = Rc::new(RefCell::new(tracks));
+        let last_progress = Rc::new(Cell::new(0.0));
+
+        let tracks_clone = tracks.clone();
+        let last_progress_clone = last_progress.clone();
+
+        // Going backwards, later children are rewound first, see Timeline
+        out.on_update = Box::new(move |p, _| {
+            let time = p * total;
+            let execute = |track: &mut Track| {
+                let elapsed = time - track.start;
+                let progress = if track.duration > 0.0 {
+                    elapsed / track.duration
+                } else if elapsed >= 0.0 {
+                    f32::INFINITY
+                } else {
+                    -1.0
+                };
+
+                track.action.execute(progress, elapsed);
+            };
+
+            let mut tracks = tracks_clone.borrow_mut();
+            if p < last_progress_clone.get() {
+                tracks.iter_mut().rev().for_each(execute);
+            } else {
+                tracks.iter_mut().for_each(execute);
+            }
+            last_progress_clone.set(p);
+        });
+
+        // Children starting at zero would only see progress 0, not a rewind
+        let tracks_clone = tracks.clone();
+        out.on_rewind = Some(Box::new(move || {
+            for track in tracks_clone.borrow_mut().iter_mut().rev() {
+                track.action.rewind();
+            }
+            last_progress.set(0.0);
+        }));
+
+        // The last update already placed the children where the composite's rate curve ends,
+        // which isn't necessarily their end state
+        out.on_stop = Box::new(move || {
+            for track in tracks.borrow_mut().iter_mut() {
+                track.action.stop();
+            }
+        });
+
+        out
+    }
+}
+
+// Plays every child at once, starting together
+#[derive(Default)]
+pub struct AnimationGroup {
+    composite: Composite,
+}
+
+impl AnimationGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<Ani: Animation + 'static>(mut self, animation: Ani, duration: &Duration) -> Self {
+        self.add(animation, duration);
+        self
+    }
+
+    pub fn add<Ani: Animation + 'static>(&mut self, animation: Ani, duration: &Duration) {
+        self.composite.push(animation, 0.0, duration.as_secs_f32());
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.composite.duration)
+    }
+}
+
+impl Animation for AnimationGroup {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        self.composite.into_action(scene)
+    }
+}
+
+// Plays the children one after another
+#[derive(Default)]
+pub struct Succession {
+    composite: Composite,
+}
+
+impl Succession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<Ani: Animation + 'static>(mut self, animation: Ani, duration: &Duration) -> Self {
+        self.add(animation, duration);
+        self
+    }
+
+    pub fn add<Ani: Animation + 'static>(&mut self, animation: Ani, duration: &Duration) {
+        let start = self.composite.duration;
+        self.composite
+            .push(animation, start, duration.as_secs_f32());
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.composite.duration)
+    }
+}
+
+impl Animation for Succession {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        self.composite.into_action(scene)
+    }
+}
+
+// Each child starts once the previous one is lag_ratio of the way through, so 0 plays them
+// together and 1 one after another
+pub struct LaggedStart {
+    pub lag_ratio: f32,
+
+    composite: Composite,
+}
+
+impl LaggedStart {
+    pub fn new(lag_ratio: f32) -> Self {
+        Self {
+            lag_ratio,
+            composite: Composite::default(),
+        }
+    }
+
+    pub fn with<Ani: Animation + 'static>(mut self, animation: Ani, duration: &Duration) -> Self {
+        self.add(animation, duration);
+        self
+    }
+
+    pub fn add<Ani: Animation + 'static>(&mut self, animation: Ani, duration: &Duration) {
+        let start = self
+            .composite
+            .last_entry()
+            .map_or(0.0, |entry| entry.start + entry.duration * self.lag_ratio);
+        self.composite
+            .push(animation, start, duration.as_secs_f32());
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(self.composite.duration)
+    }
+}
+
+impl Animation for LaggedStart {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        self.composite.into_action(scene)
+    }
+}
+
+// Does nothing for its duration, a pause inside a Succession
+#[derive(Default)]
+pub struct Wait {}
+
+impl Wait {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Animation for Wait {
+    fn into_action(self, _scene: Rc<RefCell<Scene>>) -> Action {
+        Action::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{
+        rate_functions::there_and_back,
+        testing::{Log, Probe},
+    };
+
+    fn starts(composite: &Composite) -> Vec<f32> {
+        composite.entries.iter().map(|entry| entry.start).collect()
+    }
+
+    fn build<Ani: Animation>(animation: Ani, duration: f32) -> Action {
+        let mut action = animation.into_action(Rc::new(RefCell::new(Scene::new())));
+        action.duration = duration;
+        action
+    }
+
+    #[test]
+    fn succession_plays_children_back_to_back() {
+        let (value, log) = (Rc::new(Cell::new(0.0)), Log::default());
+
+        let succession = Succession::new()
+            .with(Probe::new("a", 1.0, &value, &log), &Duration::from_secs(1))
+            .with(Wait::new(), &Duration::from_secs(2))
+            .with(Probe::new("b", 2.0, &value, &log), &Duration::from_secs(1));
+
+        assert_eq!(starts(&succession.composite), [0.0, 1.0, 3.0]);
+        assert_eq!(succession.duration(), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn lagged_start_offsets_children_by_lag_ratio() {
+        let (value, log) = (Rc::new(Cell::new(0.0)), Log::default());
+
+        let lagged = LaggedStart::new(0.5)
+            .with(Probe::new("a", 1.0, &value, &log), &Duration::from_secs(2))
+            .with(Probe::new("b", 2.0, &value, &log), &Duration::from_secs(4))
+            .with(Probe::new("c", 3.0, &value, &log), &Duration::from_secs(2));
+        assert_eq!(starts(&lagged.composite), [0.0, 1.0, 3.0]);
+        assert_eq!(lagged.duration(), Duration::from_secs(5));
+
+        let together = LaggedStart::new(0.0)
+            .with(Probe::new("a", 1.0, &value, &log), &Duration::from_secs(2))
+            .with(Probe::new("b", 2.0, &value, &log), &Duration::from_secs(3));
+        assert_eq!(starts(&together.composite), [0.0, 0.0]);
+        assert_eq!(together.duration(), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn scrubbing_back_rewinds_children_in_reverse_order() {
+        let (value, log) = (Rc::new(Cell::new(0.0)), Log::default());
+
+        let succession = Succession::new()
+            .with(Probe::new("a", 1.0, &value, &log), &Duration::from_secs(1))
+            .with(Probe::new("b", 3.0, &value, &log), &Duration::from_secs(1));
+        let mut action = build(succession, 2.0);
+
+        action.execute(0.25, 0.5);
+        action.execute(0.9, 1.8);
+        assert!((value.get() - 2.6).abs() < 1e-5);
+
+        log.borrow_mut().clear();
+        action.execute(0.0, 0.0);
+
+        assert_eq!(*log.borrow(), ["b update 0", "a update 0"]);
+        assert_eq!(value.get(), 0.0);
+    }
+
+    #[test]
+    fn stopping_keeps_children_where_the_rate_curve_left_them() {
+        let (value, log) = (Rc::new(Cell::new(0.0)), Log::default());
+
+        let group = AnimationGroup::new()
+            .with(Probe::new("a", 1.0, &value, &log), &Duration::from_secs(1))
+            .with_rate_func(there_and_back);
+        let mut action = build(group, 1.0);
+
+        action.execute(0.5, 0.5);
+        assert_eq!(value.get(), 1.0);
+
+        action.execute(0.8, 0.8);
+        log.borrow_mut().clear();
+        action.execute(1.5, 1.5);
+
+        assert!(action.is_stopped());
+        assert_eq!(*log.borrow(), ["a update 0", "a stop"]);
+        assert_eq!(value.get(), 0.0);
+    }
+}
@@ -5,7 +5,16 @@ mod action;
 pub use action::*;
 
 mod animation;
-pub use animation::Animation;
+pub use animation::{Animation, WithRateFunc};
+
+mod composition;
+pub use composition::*;
+
+#[cfg(test)]
+mod testing;
+
+pub mod rate_functions;
+pub use rate_functions::RateFunc;
 
 mod predefined;
 pub use predefined::*;
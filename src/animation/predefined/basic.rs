@@ -1,22 +1,23 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
+    MeshHandle,
     animation::{Action, Animation},
     geometry::Mesh,
 };
 use nalgebra::{UnitQuaternion, UnitVector3, Vector3};
 
 pub struct MeshAnimation {
-    pub mesh_index: usize,
+    pub mesh: MeshHandle,
     pub on_update: Box<dyn FnMut(&mut Mesh, f32, f32)>,
     pub on_start: Box<dyn FnMut()>,
     pub on_stop: Box<dyn FnMut()>,
 }
 
 impl MeshAnimation {
-    pub fn new(mesh_index: usize) -> Self {
+    pub fn new(mesh: MeshHandle) -> Self {
         Self {
-            mesh_index,
+            mesh,
             on_update: Box::new(|_, _, _| {}),
             on_start: Box::new(|| {}),
             on_stop: Box::new(|| {}),
@@ -47,11 +48,9 @@ impl Animation for MeshAnimation {
         out.on_stop = self.on_stop;
 
         out.on_update = Box::new(move |progress, elapsed_time| {
-            (self.on_update)(
-                scene.borrow_mut().get_mesh_mut(self.mesh_index),
-                progress,
-                elapsed_time,
-            )
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                (self.on_update)(mesh, progress, elapsed_time)
+            }
         });
 
         out
@@ -59,23 +58,23 @@ impl Animation for MeshAnimation {
 }
 
 pub struct RotateAxisAngle {
-    pub mesh_index: usize,
+    pub mesh: MeshHandle,
     pub axis: UnitVector3<f32>,
     pub angle_rad: f32,
 }
 
 impl RotateAxisAngle {
-    pub fn new(mesh_index: usize, axis: UnitVector3<f32>, angle_rad: f32) -> Self {
+    pub fn new(mesh: MeshHandle, axis: UnitVector3<f32>, angle_rad: f32) -> Self {
         Self {
-            mesh_index,
+            mesh,
             axis,
             angle_rad,
         }
     }
 
-    pub fn new_normalize(mesh_index: usize, axis: Vector3<f32>, angle_rad: f32) -> Self {
+    pub fn new_normalize(mesh: MeshHandle, axis: Vector3<f32>, angle_rad: f32) -> Self {
         Self {
-            mesh_index,
+            mesh,
             axis: UnitVector3::new_normalize(axis),
             angle_rad,
         }
@@ -91,18 +90,19 @@ impl Animation for RotateAxisAngle {
         let start_rotation_clone = start_rotation.clone();
 
         out.on_start = Box::new(move || {
-            start_rotation_clone
-                .borrow_mut()
-                .clone_from(scene_clone.borrow().get_mesh(self.mesh_index).rotation());
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                start_rotation_clone
+                    .borrow_mut()
+                    .clone_from(mesh.rotation());
+            }
         });
         out.on_update = Box::new(move |p, _| {
-            scene
-                .borrow_mut()
-                .get_mesh_mut(self.mesh_index)
-                .set_rotation(
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                mesh.set_rotation(
                     &(UnitQuaternion::from_axis_angle(&self.axis, self.angle_rad * p)
                         * &*start_rotation.borrow()),
                 );
+            }
         });
 
         out
@@ -0,0 +1,469 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    MeshHandle, Scene,
+    animation::{Action, Animation},
+    math::{Camera, OrthographicCamera, PerspectiveCamera},
+};
+use nalgebra::{Isometry3, Point3, UnitQuaternion, UnitVector3, Vector3};
+
+pub type CameraUpdateFn = Box<dyn FnMut(&mut dyn Camera, f32, f32)>;
+
+pub struct CameraAnimation {
+    pub on_update: CameraUpdateFn,
+    pub on_start: Box<dyn FnMut()>,
+    pub on_stop: Box<dyn FnMut()>,
+}
+
+impl CameraAnimation {
+    pub fn new() -> Self {
+        Self {
+            on_update: Box::new(|_, _, _| {}),
+            on_start: Box::new(|| {}),
+            on_stop: Box::new(|| {}),
+        }
+    }
+
+    pub fn with_on_update<F: FnMut(&mut dyn Camera, f32, f32) + 'static>(
+        mut self,
+        closure: F,
+    ) -> Self {
+        self.on_update = Box::new(closure);
+        self
+    }
+
+    pub fn with_on_start<F: FnMut() + 'static>(mut self, closure: F) -> Self {
+        self.on_start = Box::new(closure);
+        self
+    }
+
+    pub fn with_on_stop<F: FnMut() + 'static>(mut self, closure: F) -> Self {
+        self.on_stop = Box::new(closure);
+        self
+    }
+}
+
+impl Default for CameraAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for CameraAnimation {
+    fn into_action(mut self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+
+        out.on_start = self.on_start;
+        out.on_stop = self.on_stop;
+
+        out.on_update = Box::new(move |progress, elapsed_time| {
+            (self.on_update)(scene.borrow_mut().camera_mut(), progress, elapsed_time)
+        });
+
+        out
+    }
+}
+
+pub struct MoveCamera {
+    pub center: Vector3<f32>,
+    pub look_at: Option<Point3<f32>>,
+}
+
+impl MoveCamera {
+    pub fn new(center: Vector3<f32>) -> Self {
+        Self {
+            center,
+            look_at: None,
+        }
+    }
+
+    pub fn with_look_at(mut self, target: Point3<f32>) -> Self {
+        self.look_at = Some(target);
+        self
+    }
+}
+
+impl Animation for MoveCamera {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start = Rc::new(RefCell::new(Isometry3::identity()));
+
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+
+        out.on_start = Box::new(move || {
+            *start_clone.borrow_mut() = *scene_clone.borrow().camera().view_transform();
+        });
+        out.on_rewind = Some(restore_view(&scene, &start));
+        out.on_update = Box::new(move |p, _| {
+            let mut scene = scene.borrow_mut();
+            let camera = scene.camera_mut();
+
+            let start_center = start.borrow().inverse_transform_point(&Point3::origin());
+            camera.set_center(&start_center.coords.lerp(&self.center, p));
+            if let Some(target) = &self.look_at {
+                camera.look_at(target);
+            }
+        });
+
+        out
+    }
+}
+
+pub struct OrbitCamera {
+    pub target: Point3<f32>,
+    pub axis: UnitVector3<f32>,
+    pub angle_rad: f32,
+}
+
+impl OrbitCamera {
+    pub fn new(target: Point3<f32>, axis: UnitVector3<f32>, angle_rad: f32) -> Self {
+        Self {
+            target,
+            axis,
+            angle_rad,
+        }
+    }
+
+    pub fn new_normalize(target: Point3<f32>, axis: Vector3<f32>, angle_rad: f32) -> Self {
+        Self {
+            target,
+            axis: UnitVector3::new_normalize(axis),
+            angle_rad,
+        }
+    }
+}
+
+impl Animation for OrbitCamera {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start = Rc::new(RefCell::new(Isometry3::identity()));
+
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+
+        out.on_start = Box::new(move || {
+            *start_clone.borrow_mut() = *scene_clone.borrow().camera().view_transform();
+        });
+        out.on_rewind = Some(restore_view(&scene, &start));
+        out.on_update = Box::new(move |p, _| {
+            let mut scene = scene.borrow_mut();
+            let camera = scene.camera_mut();
+
+            let start_offset =
+                start.borrow().inverse_transform_point(&Point3::origin()) - self.target;
+            let offset =
+                UnitQuaternion::from_axis_angle(&self.axis, self.angle_rad * p) * start_offset;
+
+            camera.set_center(&(self.target.coords + offset));
+            camera.look_at(&self.target);
+        });
+
+        out
+    }
+}
+
+// Moves the camera along its view direction, negative distances pull it back
+pub struct DollyCamera {
+    pub distance: f32,
+}
+
+impl DollyCamera {
+    pub fn new(distance: f32) -> Self {
+        Self { distance }
+    }
+}
+
+impl Animation for DollyCamera {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start = Rc::new(RefCell::new((Vector3::zeros(), Vector3::zeros())));
+
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+
+        out.on_start = Box::new(move || {
+            let scene = scene_clone.borrow();
+            *start_clone.borrow_mut() = (*scene.camera().center(), forward(scene.camera()));
+        });
+        out.on_update = Box::new(move |p, _| {
+            let (start_center, forward) = *start.borrow();
+
+            scene
+                .borrow_mut()
+                .camera_mut()
+                .set_center(&(start_center + forward * self.distance * p));
+        });
+
+        out
+    }
+}
+
+// Only affects PerspectiveCamera
+pub struct ChangeFov {
+    pub fov_rad: f32,
+}
+
+impl ChangeFov {
+    pub fn new(fov_rad: f32) -> Self {
+        Self { fov_rad }
+    }
+}
+
+impl Animation for ChangeFov {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_fov_rad = Rc::new(RefCell::new(self.fov_rad));
+
+        let scene_clone = scene.clone();
+        let start_fov_rad_clone = start_fov_rad.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(camera) = scene_clone
+                .borrow()
+                .camera()
+                .as_any()
+                .downcast_ref::<PerspectiveCamera>()
+            {
+                *start_fov_rad_clone.borrow_mut() = camera.fov_rad();
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(camera) = scene
+                .borrow_mut()
+                .camera_mut()
+                .as_any_mut()
+                .downcast_mut::<PerspectiveCamera>()
+            {
+                let start_fov_rad = *start_fov_rad.borrow();
+                camera.set_fov_rad(start_fov_rad + (self.fov_rad - start_fov_rad) * p);
+            }
+        });
+
+        out
+    }
+}
+
+// Only affects OrthographicCamera
+pub struct ZoomCamera {
+    pub zoom: f32,
+}
+
+impl ZoomCamera {
+    pub fn new(zoom: f32) -> Self {
+        Self { zoom }
+    }
+}
+
+impl Animation for ZoomCamera {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_zoom = Rc::new(RefCell::new(self.zoom));
+
+        let scene_clone = scene.clone();
+        let start_zoom_clone = start_zoom.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(camera) = scene_clone
+                .borrow()
+                .camera()
+                .as_any()
+                .downcast_ref::<OrthographicCamera>()
+            {
+                *start_zoom_clone.borrow_mut() = camera.zoom();
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(camera) = scene
+                .borrow_mut()
+                .camera_mut()
+                .as_any_mut()
+                .downcast_mut::<OrthographicCamera>()
+            {
+                let start_zoom = *start_zoom.borrow();
+                camera.set_zoom(start_zoom + (self.zoom - start_zoom) * p);
+            }
+        });
+
+        out
+    }
+}
+
+// Keeps the view direction and moves the camera until the meshes fit in the frame
+pub struct FrameMeshes {
+    pub meshes: Vec<MeshHandle>,
+    pub margin: f32,
+}
+
+impl FrameMeshes {
+    pub fn new(meshes: Vec<MeshHandle>) -> Self {
+        Self {
+            meshes,
+            margin: 1.1,
+        }
+    }
+
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FrameState {
+    start_center: Vector3<f32>,
+    end_center: Vector3<f32>,
+    start_zoom: f32,
+    end_zoom: f32,
+}
+
+impl Animation for FrameMeshes {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let state: Rc<RefCell<Option<FrameState>>> = Rc::new(RefCell::new(None));
+
+        let scene_clone = scene.clone();
+        let state_clone = state.clone();
+
+        out.on_start = Box::new(move || {
+            let mut scene = scene_clone.borrow_mut();
+
+            let Some(bounds) = self
+                .meshes
+                .iter()
+                .filter_map(|handle| scene.world_bounding_sphere(*handle))
+                .reduce(|a, b| a.union(&b))
+            else {
+                return;
+            };
+            let sphere_center = bounds.center;
+            let radius = bounds.radius.max(f32::EPSILON) * self.margin;
+
+            let camera = scene.camera();
+            let start_center = *camera.center();
+            let forward = forward(camera);
+
+            let frame_state =
+                if let Some(camera) = camera.as_any().downcast_ref::<PerspectiveCamera>() {
+                    let half_fov_y = camera.fov_rad() / 2.0;
+                    let half_fov_x = (half_fov_y.tan() * camera.proj_transform.aspect()).atan();
+                    let distance = radius / half_fov_y.min(half_fov_x).sin();
+
+                    FrameState {
+                        start_center,
+                        end_center: sphere_center.coords - forward * distance,
+                        start_zoom: 1.0,
+                        end_zoom: 1.0,
+                    }
+                } else if let Some(camera) = camera.as_any().downcast_ref::<OrthographicCamera>() {
+                    // Keep the current depth unless the meshes would cross the camera
+                    let depth = (sphere_center.coords - start_center)
+                        .dot(&forward)
+                        .max(radius * 2.0);
+
+                    FrameState {
+                        start_center,
+                        end_center: sphere_center.coords - forward * depth,
+                        start_zoom: camera.zoom(),
+                        end_zoom: camera.frame_width().min(camera.frame_height()) / (radius * 2.0),
+                    }
+                } else {
+                    return;
+                };
+
+            *state_clone.borrow_mut() = Some(frame_state);
+        });
+        out.on_update = Box::new(move |p, _| {
+            let Some(state) = *state.borrow() else {
+                return;
+            };
+
+            let mut scene = scene.borrow_mut();
+            let camera = scene.camera_mut();
+
+            camera.set_center(&state.start_center.lerp(&state.end_center, p));
+            if let Some(camera) = camera.as_any_mut().downcast_mut::<OrthographicCamera>() {
+                camera.set_zoom(state.start_zoom + (state.end_zoom - state.start_zoom) * p);
+            }
+        });
+
+        out
+    }
+}
+
+// Looking at a target turns the camera even at progress 0, so rewinding puts the whole view back
+fn restore_view(
+    scene: &Rc<RefCell<Scene>>,
+    start: &Rc<RefCell<Isometry3<f32>>>,
+) -> Box<dyn FnMut()> {
+    let (scene, start) = (scene.clone(), start.clone());
+    Box::new(move || {
+        scene
+            .borrow_mut()
+            .camera_mut()
+            .set_view_transform(&start.borrow())
+    })
+}
+
+fn forward(camera: &dyn Camera) -> Vector3<f32> {
+    camera.view_transform().rotation.inverse() * -Vector3::z()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_view_eq(a: &Isometry3<f32>, b: &Isometry3<f32>) {
+        assert!((a.translation.vector - b.translation.vector).norm() < 1e-5);
+        assert!(a.rotation.angle_to(&b.rotation) < 1e-5);
+    }
+
+    fn rewound_view<Ani: Animation>(animation: Ani) -> (Isometry3<f32>, Isometry3<f32>) {
+        let scene = Rc::new(RefCell::new(Scene::new()));
+        scene
+            .borrow_mut()
+            .camera_mut()
+            .set_center(&Vector3::new(0.0, 0.0, 5.0));
+        let start = *scene.borrow().camera().view_transform();
+
+        let mut action = animation.into_action(scene.clone());
+        action.execute(0.0, 0.0);
+        action.execute(0.5, 0.5);
+        action.execute(-0.5, -0.5);
+
+        let end = *scene.borrow().camera().view_transform();
+        (start, end)
+    }
+
+    #[test]
+    fn rewinding_move_camera_restores_the_view() {
+        let (start, end) = rewound_view(
+            MoveCamera::new(Vector3::new(0.0, 4.0, 5.0)).with_look_at(Point3::new(0.0, -2.0, 0.0)),
+        );
+        assert_view_eq(&start, &end);
+    }
+
+    #[test]
+    fn rewinding_orbit_camera_restores_the_view() {
+        let (start, end) = rewound_view(OrbitCamera::new(
+            Point3::new(1.0, 0.0, 0.0),
+            Vector3::y_axis(),
+            1.0,
+        ));
+        assert_view_eq(&start, &end);
+    }
+
+    #[test]
+    fn orbiting_keeps_the_distance_to_the_target() {
+        let scene = Rc::new(RefCell::new(Scene::new()));
+        let target = Point3::new(1.0, 0.0, 0.0);
+        let distance = (scene.borrow().camera().center() - target.coords).norm();
+
+        let mut action =
+            OrbitCamera::new(target, Vector3::y_axis(), 1.0).into_action(scene.clone());
+        action.execute(0.5, 0.5);
+
+        let center = *scene.borrow().camera().center();
+        assert!(((center - target.coords).norm() - distance).abs() < 1e-4);
+    }
+}
@@ -1,2 +1,8 @@
 mod basic;
 pub use basic::*;
+
+mod camera;
+pub use camera::*;
+
+mod motion;
+pub use motion::*;
@@ -0,0 +1,466 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    MeshHandle, Scene,
+    animation::{Action, Animation},
+};
+use nalgebra::{Point3, UnitQuaternion, UnitVector3, Vector3};
+
+pub struct MoveTo {
+    pub mesh: MeshHandle,
+    pub position: Point3<f32>,
+}
+
+impl MoveTo {
+    pub fn new(mesh: MeshHandle, position: Point3<f32>) -> Self {
+        Self { mesh, position }
+    }
+}
+
+impl Animation for MoveTo {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_position = Rc::new(RefCell::new(Point3::origin()));
+
+        let scene_clone = scene.clone();
+        let start_position_clone = start_position.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                *start_position_clone.borrow_mut() = mesh.position();
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                let start_position = start_position.borrow();
+                mesh.set_position(&(start_position.coords.lerp(&self.position.coords, p).into()));
+            }
+        });
+
+        out
+    }
+}
+
+pub struct Shift {
+    pub mesh: MeshHandle,
+    pub offset: Vector3<f32>,
+}
+
+impl Shift {
+    pub fn new(mesh: MeshHandle, offset: Vector3<f32>) -> Self {
+        Self { mesh, offset }
+    }
+}
+
+impl Animation for Shift {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_position = Rc::new(RefCell::new(Point3::origin()));
+
+        let scene_clone = scene.clone();
+        let start_position_clone = start_position.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                *start_position_clone.borrow_mut() = mesh.position();
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                mesh.set_position(&(*start_position.borrow() + self.offset * p));
+            }
+        });
+
+        out
+    }
+}
+
+pub struct ScaleTo {
+    pub mesh: MeshHandle,
+    pub scale: Vector3<f32>,
+}
+
+impl ScaleTo {
+    pub fn new(mesh: MeshHandle, scale: Vector3<f32>) -> Self {
+        Self { mesh, scale }
+    }
+
+    pub fn new_uniform(mesh: MeshHandle, scale: f32) -> Self {
+        Self {
+            mesh,
+            scale: Vector3::repeat(scale),
+        }
+    }
+}
+
+impl Animation for ScaleTo {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_scale = Rc::new(RefCell::new(Vector3::repeat(1.0)));
+
+        let scene_clone = scene.clone();
+        let start_scale_clone = start_scale.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                start_scale_clone.borrow_mut().copy_from(mesh.scale());
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                mesh.scale_to(&start_scale.borrow().lerp(&self.scale, p));
+            }
+        });
+
+        out
+    }
+}
+
+pub struct ScaleBy {
+    pub mesh: MeshHandle,
+    pub factor: Vector3<f32>,
+}
+
+impl ScaleBy {
+    pub fn new(mesh: MeshHandle, factor: Vector3<f32>) -> Self {
+        Self { mesh, factor }
+    }
+
+    pub fn new_uniform(mesh: MeshHandle, factor: f32) -> Self {
+        Self {
+            mesh,
+            factor: Vector3::repeat(factor),
+        }
+    }
+}
+
+impl Animation for ScaleBy {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start_scale = Rc::new(RefCell::new(Vector3::repeat(1.0)));
+
+        let scene_clone = scene.clone();
+        let start_scale_clone = start_scale.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                start_scale_clone.borrow_mut().copy_from(mesh.scale());
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                let start_scale = *start_scale.borrow();
+                mesh.scale_to(&start_scale.lerp(&start_scale.component_mul(&self.factor), p));
+            }
+        });
+
+        out
+    }
+}
+
+// Orbits the mesh around a point, turning it along the way
+pub struct RotateAbout {
+    pub mesh: MeshHandle,
+    pub point: Point3<f32>,
+    pub axis: UnitVector3<f32>,
+    pub angle_rad: f32,
+}
+
+impl RotateAbout {
+    pub fn new(
+        mesh: MeshHandle,
+        point: Point3<f32>,
+        axis: UnitVector3<f32>,
+        angle_rad: f32,
+    ) -> Self {
+        Self {
+            mesh,
+            point,
+            axis,
+            angle_rad,
+        }
+    }
+
+    pub fn new_normalize(
+        mesh: MeshHandle,
+        point: Point3<f32>,
+        axis: Vector3<f32>,
+        angle_rad: f32,
+    ) -> Self {
+        Self {
+            mesh,
+            point,
+            axis: UnitVector3::new_normalize(axis),
+            angle_rad,
+        }
+    }
+}
+
+impl Animation for RotateAbout {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start = Rc::new(RefCell::new((Point3::origin(), UnitQuaternion::identity())));
+
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                *start_clone.borrow_mut() = (mesh.position(), *mesh.rotation());
+            }
+        });
+        out.on_update = Box::new(move |p, _| {
+            if let Some(mesh) = scene.borrow_mut().get_mesh_mut(self.mesh) {
+                let (start_position, start_rotation) = *start.borrow();
+
+                mesh.set_position(&start_position);
+                mesh.set_rotation(&start_rotation);
+                mesh.rotate_around(&self.point, &self.axis, self.angle_rad * p);
+            }
+        });
+
+        out
+    }
+}
+
+pub enum MotionPath {
+    Polyline(Vec<Point3<f32>>),
+    // Sampled over t in [0, 1]
+    Parametric(Box<dyn Fn(f32) -> Point3<f32>>),
+}
+
+impl MotionPath {
+    pub fn parametric<F: Fn(f32) -> Point3<f32> + 'static>(curve: F) -> Self {
+        Self::Parametric(Box::new(curve))
+    }
+
+    // Polylines are walked at constant speed along their length
+    pub fn point_at(&self, t: f32) -> Option<Point3<f32>> {
+        self.point_along(&self.cumulative_lengths(), t)
+    }
+
+    pub fn tangent_at(&self, t: f32) -> Option<Vector3<f32>> {
+        self.tangent_along(&self.cumulative_lengths(), t)
+    }
+
+    // Length of the polyline up to each of its points, empty for parametric paths
+    fn cumulative_lengths(&self) -> Vec<f32> {
+        let MotionPath::Polyline(points) = self else {
+            return Vec::new();
+        };
+
+        let mut total = 0.0;
+        let mut lengths = Vec::with_capacity(points.len());
+        lengths.push(0.0);
+        for segment in points.windows(2) {
+            total += nalgebra::distance(&segment[0], &segment[1]);
+            lengths.push(total);
+        }
+
+        lengths
+    }
+
+    fn point_along(&self, lengths: &[f32], t: f32) -> Option<Point3<f32>> {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            MotionPath::Polyline(points) => {
+                let (index, local_t) = Self::polyline_segment(points, lengths, t)?;
+                match points.get(index + 1) {
+                    Some(next) => Some(points[index] + (next - points[index]) * local_t),
+                    None => Some(points[index]),
+                }
+            }
+            MotionPath::Parametric(curve) => Some(curve(t)),
+        }
+    }
+
+    fn tangent_along(&self, lengths: &[f32], t: f32) -> Option<Vector3<f32>> {
+        const STEP: f32 = 1e-3;
+
+        let tangent = match self {
+            MotionPath::Polyline(points) => {
+                let (index, _) = Self::polyline_segment(points, lengths, t.clamp(0.0, 1.0))?;
+                let index = index.min(points.len().checked_sub(2)?);
+                points[index + 1] - points[index]
+            }
+            MotionPath::Parametric(_) => {
+                let (before, after) = ((t - STEP).max(0.0), (t + STEP).min(1.0));
+                self.point_along(lengths, after)? - self.point_along(lengths, before)?
+            }
+        };
+
+        tangent.try_normalize(f32::EPSILON)
+    }
+
+    // Segment index and the position within it for a fraction of the total length
+    fn polyline_segment(points: &[Point3<f32>], lengths: &[f32], t: f32) -> Option<(usize, f32)> {
+        if points.is_empty() {
+            return None;
+        }
+
+        let total = lengths.last().copied().unwrap_or(0.0);
+        if total <= 0.0 {
+            return Some((0, 0.0));
+        }
+
+        let distance = t * total;
+        let index = lengths[1..]
+            .partition_point(|length| *length < distance)
+            .min(lengths.len() - 2);
+
+        let length = lengths[index + 1] - lengths[index];
+        let local_t = if length > 0.0 {
+            (distance - lengths[index]) / length
+        } else {
+            0.0
+        };
+
+        Some((index, local_t.clamp(0.0, 1.0)))
+    }
+}
+
+// Puts the mesh on the path as soon as it starts. With an up vector set, the mesh's local +Z
+// axis follows the tangent
+pub struct MoveAlongPath {
+    pub mesh: MeshHandle,
+    pub path: MotionPath,
+    pub orient_up: Option<Vector3<f32>>,
+}
+
+impl MoveAlongPath {
+    pub fn new(mesh: MeshHandle, path: MotionPath) -> Self {
+        Self {
+            mesh,
+            path,
+            orient_up: None,
+        }
+    }
+
+    pub fn with_orientation(mut self, up: Vector3<f32>) -> Self {
+        self.orient_up = Some(up);
+        self
+    }
+}
+
+impl Animation for MoveAlongPath {
+    fn into_action(self, scene: Rc<RefCell<Scene>>) -> Action {
+        let mut out = Action::new();
+        let start = Rc::new(RefCell::new((Point3::origin(), UnitQuaternion::identity())));
+        let lengths = self.path.cumulative_lengths();
+
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+
+        out.on_start = Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow().get_mesh(self.mesh) {
+                *start_clone.borrow_mut() = (mesh.position(), *mesh.rotation());
+            }
+        });
+
+        // The path's first point isn't where the mesh was before the start
+        let scene_clone = scene.clone();
+        let start_clone = start.clone();
+        out.on_rewind = Some(Box::new(move || {
+            if let Some(mesh) = scene_clone.borrow_mut().get_mesh_mut(self.mesh) {
+                let (start_position, start_rotation) = *start_clone.borrow();
+                mesh.set_position(&start_position);
+                mesh.set_rotation(&start_rotation);
+            }
+        }));
+
+        out.on_update = Box::new(move |p, _| {
+            let mut scene = scene.borrow_mut();
+            let Some(mesh) = scene.get_mesh_mut(self.mesh) else {
+                return;
+            };
+
+            let Some(position) = self.path.point_along(&lengths, p) else {
+                return;
+            };
+            mesh.set_position(&position);
+
+            let tangent = self.path.tangent_along(&lengths, p);
+            if let (Some(up), Some(tangent)) = (&self.orient_up, tangent) {
+                mesh.look_at(&(position + tangent), up);
+            }
+        });
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        animation::AnimationGroup,
+        geometry::{Cube, CubeDescriptor, Mesh},
+        material::BasicMaterial,
+    };
+    use std::time::Duration;
+
+    fn scene_with_cube_at(position: Point3<f32>) -> (Rc<RefCell<Scene>>, MeshHandle) {
+        let mut mesh = Mesh::new(Cube::new(&CubeDescriptor::default()), BasicMaterial {});
+        mesh.set_position(&position);
+
+        let mut scene = Scene::new();
+        let handle = scene.add_mesh(mesh);
+        (Rc::new(RefCell::new(scene)), handle)
+    }
+
+    fn bent_path() -> MotionPath {
+        MotionPath::Polyline(vec![
+            Point3::origin(),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 3.0, 0.0),
+        ])
+    }
+
+    fn position(scene: &Rc<RefCell<Scene>>, handle: MeshHandle) -> Point3<f32> {
+        scene.borrow().get_mesh(handle).unwrap().position()
+    }
+
+    #[test]
+    fn polylines_are_walked_at_constant_speed() {
+        let path = bent_path();
+
+        assert_eq!(path.point_at(0.0), Some(Point3::origin()));
+        assert_eq!(path.point_at(0.5), Some(Point3::new(1.0, 1.0, 0.0)));
+        assert_eq!(path.point_at(1.0), Some(Point3::new(1.0, 3.0, 0.0)));
+        assert_eq!(path.tangent_at(0.1), Some(Vector3::x()));
+        assert_eq!(path.tangent_at(0.9), Some(Vector3::y()));
+    }
+
+    #[test]
+    fn rewinding_puts_the_mesh_back_where_it_started() {
+        let start = Point3::new(5.0, 5.0, 5.0);
+        let (scene, handle) = scene_with_cube_at(start);
+        let mut action = MoveAlongPath::new(handle, bent_path()).into_action(scene.clone());
+
+        action.execute(0.5, 0.5);
+        assert_eq!(position(&scene, handle), Point3::new(1.0, 1.0, 0.0));
+
+        // Progress 0 is the start of the path, not a rewind
+        action.execute(0.0, 0.0);
+        assert_eq!(position(&scene, handle), Point3::origin());
+
+        action.execute(-0.5, -0.5);
+        assert_eq!(position(&scene, handle), start);
+    }
+
+    #[test]
+    fn rewinding_a_composite_rewinds_children_starting_with_it() {
+        let start = Point3::new(5.0, 5.0, 5.0);
+        let (scene, handle) = scene_with_cube_at(start);
+        let group = AnimationGroup::new().with(
+            MoveAlongPath::new(handle, bent_path()),
+            &Duration::from_secs(1),
+        );
+        let mut action = group.into_action(scene.clone());
+
+        action.execute(0.5, 0.5);
+        action.execute(-0.5, -0.5);
+        assert_eq!(position(&scene, handle), start);
+    }
+}
@@ -0,0 +1,208 @@
+use std::{f32::consts::PI, rc::Rc};
+
+// Maps the linear progress of an action, 0 at its start and 1 at its end, to the progress
+// handed to on_update. Curves may leave [0, 1] in between, as elastic does
+pub type RateFunc = Rc<dyn Fn(f32) -> f32>;
+
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+// Same sigmoid as Manim's smooth, with an inflection of 10
+pub fn smooth(t: f32) -> f32 {
+    const INFLECTION: f32 = 10.0;
+
+    let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+    let error = sigmoid(-INFLECTION / 2.0);
+
+    ((sigmoid(INFLECTION * (t - 0.5)) - error) / (1.0 - 2.0 * error)).clamp(0.0, 1.0)
+}
+
+pub fn rush_into(t: f32) -> f32 {
+    2.0 * smooth(t / 2.0)
+}
+
+pub fn rush_from(t: f32) -> f32 {
+    2.0 * smooth(t / 2.0 + 0.5) - 1.0
+}
+
+pub fn there_and_back(t: f32) -> f32 {
+    let t = if t < 0.5 { 2.0 * t } else { 2.0 * (1.0 - t) };
+    smooth(t)
+}
+
+pub fn ease_in_cubic(t: f32) -> f32 {
+    t * t * t
+}
+
+pub fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+pub fn ease_out_elastic(t: f32) -> f32 {
+    const PERIOD: f32 = 2.0 * PI / 3.0;
+
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * PERIOD).sin() + 1.0
+    }
+}
+
+pub fn ease_out_bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+// CSS-style curve through (0, 0), (x1, y1), (x2, y2) and (1, 1), x1 and x2 are kept in [0, 1]
+pub fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32) -> impl Fn(f32) -> f32 + 'static {
+    let (x1, x2) = (x1.clamp(0.0, 1.0), x2.clamp(0.0, 1.0));
+
+    let bezier = |p1: f32, p2: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+    };
+    let bezier_slope = |p1: f32, p2: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * r * r * p1 + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+    };
+
+    move |t: f32| {
+        let t = t.clamp(0.0, 1.0);
+
+        // Newton's method converges fast on most curves, bisection covers the flat ones
+        let mut s = t;
+        for _ in 0..8 {
+            let error = bezier(x1, x2, s) - t;
+            let slope = bezier_slope(x1, x2, s);
+            if error.abs() < 1e-6 || slope.abs() < 1e-6 {
+                break;
+            }
+            s = (s - error / slope).clamp(0.0, 1.0);
+        }
+
+        if (bezier(x1, x2, s) - t).abs() >= 1e-6 {
+            let (mut low, mut high) = (0.0, 1.0);
+            for _ in 0..32 {
+                s = (low + high) / 2.0;
+                if bezier(x1, x2, s) < t {
+                    low = s;
+                } else {
+                    high = s;
+                }
+            }
+        }
+
+        bezier(y1, y2, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn samples() -> impl Iterator<Item = f32> {
+        (0..=100).map(|i| i as f32 / 100.0)
+    }
+
+    fn monotone_curves() -> Vec<(&'static str, RateFunc)> {
+        vec![
+            ("linear", Rc::new(linear)),
+            ("smooth", Rc::new(smooth)),
+            ("rush_into", Rc::new(rush_into)),
+            ("rush_from", Rc::new(rush_from)),
+            ("ease_in_cubic", Rc::new(ease_in_cubic)),
+            ("ease_out_cubic", Rc::new(ease_out_cubic)),
+            ("ease_in_out_cubic", Rc::new(ease_in_out_cubic)),
+            ("ease", Rc::new(cubic_bezier(0.25, 0.1, 0.25, 1.0))),
+            ("ease_in_out", Rc::new(cubic_bezier(0.42, 0.0, 0.58, 1.0))),
+        ]
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        let mut curves = monotone_curves();
+        curves.push(("ease_out_elastic", Rc::new(ease_out_elastic)));
+        curves.push(("ease_out_bounce", Rc::new(ease_out_bounce)));
+
+        for (name, curve) in curves {
+            assert!(curve(0.0).abs() < EPSILON, "{name}(0) = {}", curve(0.0));
+            assert!(
+                (curve(1.0) - 1.0).abs() < EPSILON,
+                "{name}(1) = {}",
+                curve(1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn there_and_back_returns_to_zero() {
+        assert!(there_and_back(0.0).abs() < EPSILON);
+        assert!((there_and_back(0.5) - 1.0).abs() < EPSILON);
+        assert!(there_and_back(1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn monotone_curves_never_go_back() {
+        for (name, curve) in monotone_curves() {
+            let values: Vec<f32> = samples().map(|t| curve(t)).collect();
+            for pair in values.windows(2) {
+                assert!(pair[1] >= pair[0] - EPSILON, "{name} goes back: {pair:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_matches_css_ease() {
+        let ease = cubic_bezier(0.25, 0.1, 0.25, 1.0);
+        assert!((ease(0.5) - 0.802_403_4).abs() < 1e-4);
+
+        let ease_in_out = cubic_bezier(0.42, 0.0, 0.58, 1.0);
+        assert!((ease_in_out(0.5) - 0.5).abs() < EPSILON);
+    }
+
+    // Flat stretches stall Newton's method, the bisection fallback has to land on the curve
+    #[test]
+    fn cubic_bezier_stays_on_flat_curves() {
+        let (x1, y1, x2, y2) = (0.0, 1.0, 1.0, 0.0);
+        let curve = cubic_bezier(x1, y1, x2, y2);
+        let bezier = |p1: f32, p2: f32, s: f32| {
+            let r = 1.0 - s;
+            3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+        };
+
+        for s in samples() {
+            let (x, y) = (bezier(x1, x2, s), bezier(y1, y2, s));
+            assert!(
+                (curve(x) - y).abs() < 1e-3,
+                "at s = {s}: {} != {y}",
+                curve(x)
+            );
+        }
+    }
+}
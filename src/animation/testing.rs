@@ -0,0 +1,64 @@
+use crate::{
+    Scene,
+    animation::{Action, Animation},
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+pub(crate) type Log = Rc<RefCell<Vec<String>>>;
+
+// Moves a shared value from wherever it was at start to `target`, logging every callback
+pub(crate) struct Probe {
+    pub name: &'static str,
+    pub target: f32,
+    pub value: Rc<Cell<f32>>,
+    pub log: Log,
+}
+
+impl Probe {
+    pub fn new(name: &'static str, target: f32, value: &Rc<Cell<f32>>, log: &Log) -> Self {
+        Self {
+            name,
+            target,
+            value: value.clone(),
+            log: log.clone(),
+        }
+    }
+
+    // Doesn't need a scene, for driving timelines directly
+    pub fn action(self) -> Action {
+        let Self {
+            name,
+            target,
+            value,
+            log,
+        } = self;
+        let start = Rc::new(Cell::new(0.0));
+
+        let mut out = Action::new();
+
+        let (start_clone, value_clone, log_clone) = (start.clone(), value.clone(), log.clone());
+        out.on_start = Box::new(move || {
+            start_clone.set(value_clone.get());
+            log_clone.borrow_mut().push(format!("{name} start"));
+        });
+
+        let log_clone = log.clone();
+        out.on_stop = Box::new(move || log_clone.borrow_mut().push(format!("{name} stop")));
+
+        out.on_update = Box::new(move |p, _| {
+            value.set(start.get() + (target - start.get()) * p);
+            log.borrow_mut().push(format!("{name} update {p}"));
+        });
+
+        out
+    }
+}
+
+impl Animation for Probe {
+    fn into_action(self, _scene: Rc<RefCell<Scene>>) -> Action {
+        self.action()
+    }
+}
@@ -1,6 +1,7 @@
 use crate::animation::Action;
+use std::{cell::Cell, rc::Rc, time::Instant};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum TimelineState {
     PLAYING,
     PAUSED,
@@ -10,15 +11,43 @@ pub enum TimelineState {
 pub trait Timeline {
     fn start_time(&self) -> f32;
     fn stop_time(&self) -> f32;
+    // Absolute, never earlier than start_time
     fn current_time(&self) -> f32;
+    fn logical_fps(&self) -> f32;
     fn state(&self) -> &TimelineState;
 
     fn start(&mut self);
     fn forward(&mut self);
+    fn step_backward(&mut self);
     fn pause(&mut self);
+    fn resume(&mut self);
+
+    fn seek(&mut self, time: f32);
+    fn reset(&mut self);
+
+    // Negative speeds play backwards
+    fn speed(&self) -> f32;
+    fn set_speed(&mut self, speed: f32);
 
     fn actions(&self) -> &Vec<Action>;
     fn add_action(&mut self, action: Action);
+    fn take_actions(&mut self) -> Vec<Action>;
+}
+
+// Going backwards, later actions are rewound first so each one lands on the state its
+// predecessors left behind
+fn process_actions(actions: &mut [Action], previous_time: f32, current_time: f32) {
+    let execute = |action: &mut Action| {
+        let elapsed = current_time - action.start_time;
+        let progress = elapsed / action.duration;
+        action.execute(progress, elapsed);
+    };
+
+    if current_time < previous_time {
+        actions.iter_mut().rev().for_each(execute);
+    } else {
+        actions.iter_mut().for_each(execute);
+    }
 }
 
 pub struct LogicalTimeline {
@@ -26,8 +55,11 @@ pub struct LogicalTimeline {
     pub start_time: f32,
     pub stop_time: f32,
     pub logical_fps: f32,
-    pub current_frame: i32,
 
+    // Timeline time covered since start_time
+    played_time: f32,
+    processed_time: f32,
+    speed: f32,
     actions: Vec<Action>,
 }
 
@@ -38,24 +70,28 @@ impl LogicalTimeline {
             start_time: 0.0,
             stop_time: 0.0,
             logical_fps: 60.0,
-            current_frame: 0,
+
+            played_time: 0.0,
+            processed_time: 0.0,
+            speed: 1.0,
             actions: Vec::new(),
         }
     }
 
     fn process(&mut self) {
         let current_time = self.current_time();
-        for action in &mut self.actions {
-            let elapsed = current_time - action.start_time;
-            let progress = elapsed / action.duration;
-            action.execute(progress, elapsed);
-        }
+        process_actions(&mut self.actions, self.processed_time, current_time);
+        self.processed_time = current_time;
     }
 }
 
 impl Timeline for LogicalTimeline {
     fn current_time(&self) -> f32 {
-        (self.current_frame as f32) * (1.0 / self.logical_fps)
+        self.start_time + self.played_time
+    }
+
+    fn logical_fps(&self) -> f32 {
+        self.logical_fps
     }
 
     fn start_time(&self) -> f32 {
@@ -76,14 +112,45 @@ impl Timeline for LogicalTimeline {
     }
 
     fn forward(&mut self) {
-        self.current_frame += 1;
+        if let TimelineState::PAUSED = self.state {
+            return;
+        }
+
+        self.seek(self.current_time() + self.speed / self.logical_fps);
+    }
+
+    fn step_backward(&mut self) {
+        self.seek(self.current_time() - 1.0 / self.logical_fps);
+    }
+
+    fn seek(&mut self, time: f32) {
+        self.played_time = (time - self.start_time).max(0.0);
         self.process();
     }
 
+    fn reset(&mut self) {
+        self.seek(self.start_time);
+        self.state = TimelineState::WAITING;
+    }
+
+    fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
     fn pause(&mut self) {
         self.state = TimelineState::PAUSED;
     }
 
+    fn resume(&mut self) {
+        if let TimelineState::PAUSED = self.state {
+            self.state = TimelineState::PLAYING;
+        }
+    }
+
     fn actions(&self) -> &Vec<Action> {
         &self.actions
     }
@@ -95,6 +162,461 @@ impl Timeline for LogicalTimeline {
 
         self.actions.push(action);
     }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        self.stop_time = self.start_time;
+        std::mem::take(&mut self.actions)
+    }
 }
 
-pub struct PhysicalTimeline {}
+// Seconds since an arbitrary but fixed origin
+pub trait Clock {
+    fn now(&self) -> f32;
+}
+
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> f32 {
+        self.origin.elapsed().as_secs_f32()
+    }
+}
+
+// Only moves when told to, clones share the same time
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    time: Rc<Cell<f32>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, time: f32) {
+        self.time.set(time);
+    }
+
+    pub fn advance(&self, delta_time: f32) {
+        self.time.set(self.time.get() + delta_time);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> f32 {
+        self.time.get()
+    }
+}
+
+pub struct PhysicalTimeline {
+    pub state: TimelineState,
+    pub start_time: f32,
+    pub stop_time: f32,
+    pub logical_fps: f32,
+
+    clock: Box<dyn Clock>,
+    // Timeline time covered before the last start, resume, seek or speed change
+    played_time: f32,
+    resumed_at: f32,
+    processed_time: f32,
+    speed: f32,
+    actions: Vec<Action>,
+}
+
+impl PhysicalTimeline {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::new())
+    }
+
+    pub fn with_clock<C: Clock + 'static>(clock: C) -> Self {
+        Self {
+            state: TimelineState::WAITING,
+            start_time: 0.0,
+            stop_time: 0.0,
+            logical_fps: 60.0,
+
+            clock: Box::new(clock),
+            played_time: 0.0,
+            resumed_at: 0.0,
+            processed_time: 0.0,
+            speed: 1.0,
+            actions: Vec::new(),
+        }
+    }
+
+    fn process(&mut self) {
+        let current_time = self.current_time();
+        process_actions(&mut self.actions, self.processed_time, current_time);
+        self.processed_time = current_time;
+    }
+
+    // Folds the time played since the last resume into played_time. Playing backwards stops
+    // at the start, so a later forward run doesn't first have to make up the overshoot
+    fn settle(&mut self) {
+        let now = self.clock.now();
+        if let TimelineState::PLAYING = self.state {
+            self.played_time = (self.played_time + (now - self.resumed_at) * self.speed).max(0.0);
+        }
+        self.resumed_at = now;
+    }
+}
+
+impl Default for PhysicalTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timeline for PhysicalTimeline {
+    fn current_time(&self) -> f32 {
+        let playing_time = match self.state {
+            TimelineState::PLAYING => (self.clock.now() - self.resumed_at) * self.speed,
+            _ => 0.0,
+        };
+
+        (self.start_time + self.played_time + playing_time).max(self.start_time)
+    }
+
+    fn logical_fps(&self) -> f32 {
+        self.logical_fps
+    }
+
+    fn start_time(&self) -> f32 {
+        self.start_time
+    }
+
+    fn stop_time(&self) -> f32 {
+        self.stop_time
+    }
+
+    fn state(&self) -> &TimelineState {
+        &self.state
+    }
+
+    // Starting again while playing keeps the time played so far
+    fn start(&mut self) {
+        self.settle();
+        self.state = TimelineState::PLAYING;
+        self.process();
+    }
+
+    // The clock starts on the first frame if start was never called
+    fn forward(&mut self) {
+        match self.state {
+            TimelineState::WAITING => self.start(),
+            TimelineState::PLAYING => self.process(),
+            TimelineState::PAUSED => {}
+        }
+    }
+
+    fn step_backward(&mut self) {
+        self.seek(self.current_time() - 1.0 / self.logical_fps);
+    }
+
+    fn pause(&mut self) {
+        self.settle();
+        self.state = TimelineState::PAUSED;
+    }
+
+    fn resume(&mut self) {
+        if let TimelineState::PAUSED = self.state {
+            self.resumed_at = self.clock.now();
+            self.state = TimelineState::PLAYING;
+        }
+    }
+
+    fn seek(&mut self, time: f32) {
+        self.settle();
+        self.played_time = time.max(self.start_time) - self.start_time;
+        self.process();
+    }
+
+    fn reset(&mut self) {
+        self.seek(self.start_time);
+        self.state = TimelineState::WAITING;
+    }
+
+    fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        self.settle();
+        self.speed = speed;
+    }
+
+    fn actions(&self) -> &Vec<Action> {
+        &self.actions
+    }
+
+    fn add_action(&mut self, action: Action) {
+        if action.duration + action.start_time > self.stop_time {
+            self.stop_time = action.duration + action.start_time;
+        }
+
+        self.actions.push(action);
+    }
+
+    fn take_actions(&mut self) -> Vec<Action> {
+        self.stop_time = self.start_time;
+        std::mem::take(&mut self.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::testing::{Log, Probe};
+
+    // Records the latest progress the action was updated with
+    fn tracking_action(start_time: f32, duration: f32) -> (Action, Rc<Cell<f32>>) {
+        let progress = Rc::new(Cell::new(-1.0));
+        let progress_clone = progress.clone();
+
+        let mut action = Action::new();
+        action.start_time = start_time;
+        action.duration = duration;
+        action.on_update = Box::new(move |p, _| progress_clone.set(p));
+
+        (action, progress)
+    }
+
+    // Two back to back actions, the second one picking up where the first one left the value
+    fn recorded_timeline() -> (LogicalTimeline, Rc<Cell<f32>>, Log) {
+        let value = Rc::new(Cell::new(0.0));
+        let log = Log::default();
+
+        let mut timeline = LogicalTimeline::new();
+        timeline.logical_fps = 4.0;
+        for (name, start_time, target) in [("a", 0.5, 1.0), ("b", 1.5, 3.0)] {
+            let mut action = Probe::new(name, target, &value, &log).action();
+            action.start_time = start_time;
+            timeline.add_action(action);
+        }
+
+        (timeline, value, log)
+    }
+
+    fn play_until(timeline: &mut LogicalTimeline, time: f32) {
+        timeline.start();
+        while timeline.current_time() < time {
+            timeline.forward();
+        }
+    }
+
+    #[test]
+    fn replaying_after_a_full_rewind_matches_the_first_run() {
+        let (mut straight, straight_value, straight_log) = recorded_timeline();
+        play_until(&mut straight, 3.0);
+
+        let (mut scrubbed, scrubbed_value, scrubbed_log) = recorded_timeline();
+        play_until(&mut scrubbed, 3.0);
+        scrubbed.seek(0.0);
+        assert_eq!(scrubbed_value.get(), 0.0);
+
+        scrubbed_log.borrow_mut().clear();
+        play_until(&mut scrubbed, 3.0);
+
+        assert_eq!(*scrubbed_log.borrow(), *straight_log.borrow());
+        assert_eq!(scrubbed_value.get(), straight_value.get());
+        assert_eq!(scrubbed_value.get(), 3.0);
+    }
+
+    #[test]
+    fn replaying_after_a_partial_rewind_matches_the_first_run() {
+        let (mut straight, straight_value, straight_log) = recorded_timeline();
+        play_until(&mut straight, 1.0);
+        let replayed_from = straight_log.borrow().len();
+        play_until(&mut straight, 3.0);
+
+        let (mut scrubbed, scrubbed_value, scrubbed_log) = recorded_timeline();
+        play_until(&mut scrubbed, 3.0);
+        scrubbed.seek(1.0);
+        assert_eq!(scrubbed_value.get(), 0.5);
+
+        scrubbed_log.borrow_mut().clear();
+        play_until(&mut scrubbed, 3.0);
+
+        assert_eq!(
+            *scrubbed_log.borrow(),
+            straight_log.borrow()[replayed_from..]
+        );
+        assert_eq!(scrubbed_value.get(), straight_value.get());
+    }
+
+    #[test]
+    fn rewinding_processes_later_actions_first() {
+        let (mut timeline, value, log) = recorded_timeline();
+        play_until(&mut timeline, 3.0);
+
+        log.borrow_mut().clear();
+        timeline.seek(0.0);
+
+        assert_eq!(*log.borrow(), ["b update 0", "a update 0"]);
+        assert_eq!(value.get(), 0.0);
+    }
+
+    #[test]
+    fn stepping_backward_frame_by_frame_restores_the_start() {
+        let (mut timeline, value, _) = recorded_timeline();
+        play_until(&mut timeline, 3.0);
+
+        while timeline.current_time() > timeline.start_time() {
+            timeline.step_backward();
+        }
+        assert_eq!(value.get(), 0.0);
+
+        play_until(&mut timeline, 3.0);
+        assert_eq!(value.get(), 3.0);
+    }
+
+    #[test]
+    fn physical_timeline_follows_the_clock_from_start() {
+        let clock = ManualClock::new();
+        clock.set(5.0);
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+
+        clock.advance(1.0);
+        assert_eq!(timeline.current_time(), 0.0);
+
+        timeline.start();
+        clock.advance(0.5);
+        assert_eq!(timeline.current_time(), 0.5);
+    }
+
+    #[test]
+    fn physical_timeline_stands_still_while_paused() {
+        let clock = ManualClock::new();
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+        let (action, progress) = tracking_action(0.0, 2.0);
+        timeline.add_action(action);
+
+        timeline.start();
+        clock.advance(0.5);
+        timeline.forward();
+        assert_eq!(progress.get(), 0.25);
+
+        timeline.pause();
+        clock.advance(10.0);
+        timeline.forward();
+        assert_eq!(timeline.current_time(), 0.5);
+        assert_eq!(progress.get(), 0.25);
+
+        timeline.resume();
+        clock.advance(0.5);
+        timeline.forward();
+        assert_eq!(timeline.current_time(), 1.0);
+        assert_eq!(progress.get(), 0.5);
+    }
+
+    #[test]
+    fn physical_timeline_keeps_its_time_when_started_again() {
+        let clock = ManualClock::new();
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+
+        timeline.start();
+        clock.advance(2.0);
+        timeline.start();
+        assert_eq!(timeline.current_time(), 2.0);
+
+        clock.advance(1.0);
+        assert_eq!(timeline.current_time(), 3.0);
+    }
+
+    #[test]
+    fn physical_timeline_starts_on_the_first_forward() {
+        let clock = ManualClock::new();
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+
+        clock.advance(3.0);
+        timeline.forward();
+        assert!(matches!(timeline.state(), TimelineState::PLAYING));
+
+        clock.advance(0.25);
+        assert_eq!(timeline.current_time(), 0.25);
+    }
+
+    #[test]
+    fn physical_timeline_scales_elapsed_time_by_speed() {
+        let clock = ManualClock::new();
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+
+        timeline.start();
+        clock.advance(1.0);
+        timeline.set_speed(2.0);
+        clock.advance(1.0);
+        assert_eq!(timeline.current_time(), 3.0);
+    }
+
+    #[test]
+    fn physical_timeline_stops_at_the_start_when_played_backwards() {
+        let clock = ManualClock::new();
+        let mut timeline = PhysicalTimeline::with_clock(clock.clone());
+
+        timeline.start();
+        timeline.set_speed(-1.0);
+        clock.advance(5.0);
+        timeline.pause();
+        assert_eq!(timeline.current_time(), 0.0);
+
+        timeline.resume();
+        timeline.set_speed(1.0);
+        clock.advance(0.5);
+        assert_eq!(timeline.current_time(), 0.5);
+    }
+
+    #[test]
+    fn both_timelines_report_absolute_time() {
+        let clock = ManualClock::new();
+        let mut physical = PhysicalTimeline::with_clock(clock.clone());
+        let mut logical = LogicalTimeline::new();
+        physical.start_time = 2.0;
+        logical.start_time = 2.0;
+
+        assert_eq!(physical.current_time(), 2.0);
+        assert_eq!(logical.current_time(), 2.0);
+
+        physical.start();
+        logical.start();
+        clock.advance(1.0);
+        for _ in 0..60 {
+            logical.forward();
+        }
+        assert!((physical.current_time() - 3.0).abs() < 1e-4);
+        assert!((logical.current_time() - 3.0).abs() < 1e-4);
+
+        physical.seek(0.0);
+        logical.seek(0.0);
+        assert_eq!(physical.current_time(), 2.0);
+        assert_eq!(logical.current_time(), 2.0);
+    }
+
+    #[test]
+    fn taken_actions_leave_the_timeline_empty() {
+        let mut timeline = LogicalTimeline::new();
+        let (action, _) = tracking_action(1.0, 2.0);
+        timeline.add_action(action);
+        assert_eq!(timeline.stop_time(), 3.0);
+
+        let actions = timeline.take_actions();
+        assert_eq!(actions.len(), 1);
+        assert!(timeline.actions().is_empty());
+        assert_eq!(timeline.stop_time(), timeline.start_time());
+    }
+}
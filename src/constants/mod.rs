@@ -29,3 +29,18 @@ pub const COLOR_ATTR_INDEX: GadgetIndex = GadgetIndex {
     group_index: 1,
     binding_index: 1,
 };
+
+pub const INSTANCE_TRANSFORM_ATTR_LABEL: &str = "mraphics-instance-transform-attribute";
+pub const INSTANCE_TRANSFORM_ATTR_INDEX: GadgetIndex = GadgetIndex {
+    group_index: 2,
+    binding_index: 0,
+};
+
+pub const INSTANCE_COLOR_ATTR_LABEL: &str = "mraphics-instance-color-attribute";
+pub const INSTANCE_COLOR_ATTR_INDEX: GadgetIndex = GadgetIndex {
+    group_index: 2,
+    binding_index: 1,
+};
+
+// Index buffers are bound directly, not through a bind group
+pub const INDEX_LABEL: &str = "mraphics-index";
@@ -0,0 +1,29 @@
+use crate::math::Camera;
+use nalgebra::Vector3;
+use winit::event::{MouseScrollDelta, WindowEvent};
+
+pub trait CameraController {
+    fn handle_event(&mut self, event: &WindowEvent, camera: &mut dyn Camera) -> bool;
+
+    fn update(&mut self, _camera: &mut dyn Camera, _delta_time: f32) {}
+}
+
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
+pub(crate) fn scroll_lines(delta: &MouseScrollDelta) -> f32 {
+    match delta {
+        MouseScrollDelta::LineDelta(_, y) => *y,
+        MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_SCROLL_LINE,
+    }
+}
+
+// Right, up and forward directions of the camera in world space
+pub(crate) fn camera_basis(camera: &dyn Camera) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>) {
+    let inverse_rotation = camera.view_transform().rotation.inverse();
+
+    (
+        inverse_rotation * Vector3::x(),
+        inverse_rotation * Vector3::y(),
+        inverse_rotation * -Vector3::z(),
+    )
+}
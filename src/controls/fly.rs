@@ -0,0 +1,129 @@
+use crate::{
+    controls::{CameraController, controller::camera_basis},
+    math::Camera,
+};
+use nalgebra::{Point3, UnitQuaternion, UnitVector3, Vector3};
+use std::collections::HashSet;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+// Keeps the view direction from lining up with the up vector
+const MAX_PITCH_COS: f32 = 0.999;
+
+pub struct FlyControls {
+    pub move_speed: f32,
+    pub look_speed: f32,
+
+    looking: bool,
+    cursor: Option<PhysicalPosition<f64>>,
+    pressed_keys: HashSet<KeyCode>,
+}
+
+impl FlyControls {
+    pub fn new() -> Self {
+        Self {
+            move_speed: 2.0,
+            look_speed: 0.003,
+
+            looking: false,
+            cursor: None,
+            pressed_keys: HashSet::new(),
+        }
+    }
+
+    pub fn look(&self, camera: &mut dyn Camera, delta_x: f32, delta_y: f32) {
+        let (right, _, forward) = camera_basis(camera);
+        let up = UnitVector3::new_normalize(*camera.up());
+
+        let yaw = UnitQuaternion::from_axis_angle(&up, -delta_x * self.look_speed);
+        let pitch = UnitQuaternion::from_axis_angle(
+            &UnitVector3::new_normalize(right),
+            -delta_y * self.look_speed,
+        );
+
+        let pitched = pitch * forward;
+        let direction = if pitched.normalize().dot(&up).abs() < MAX_PITCH_COS {
+            yaw * pitched
+        } else {
+            yaw * forward
+        };
+
+        let center = *camera.center();
+        camera.look_at(&Point3::from(center + direction));
+    }
+
+    fn is_pressed(&self, key: KeyCode) -> f32 {
+        if self.pressed_keys.contains(&key) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for FlyControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController for FlyControls {
+    fn handle_event(&mut self, event: &WindowEvent, camera: &mut dyn Camera) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                let PhysicalKey::Code(key) = event.physical_key else {
+                    return false;
+                };
+
+                match event.state {
+                    ElementState::Pressed => self.pressed_keys.insert(key),
+                    ElementState::Released => self.pressed_keys.remove(&key),
+                };
+
+                true
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.looking = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if let (true, Some(cursor)) = (self.looking, self.cursor) {
+                    let delta_x = (position.x - cursor.x) as f32;
+                    let delta_y = (position.y - cursor.y) as f32;
+
+                    self.look(camera, delta_x, delta_y);
+                }
+
+                self.cursor = Some(*position);
+
+                self.looking
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut dyn Camera, delta_time: f32) {
+        let (right, _, forward) = camera_basis(camera);
+        let up = *camera.up();
+
+        // WASD to move, Space and Shift to rise and sink
+        let direction: Vector3<f32> = forward
+            * (self.is_pressed(KeyCode::KeyW) - self.is_pressed(KeyCode::KeyS))
+            + right * (self.is_pressed(KeyCode::KeyD) - self.is_pressed(KeyCode::KeyA))
+            + up * (self.is_pressed(KeyCode::Space) - self.is_pressed(KeyCode::ShiftLeft));
+
+        if direction.norm_squared() == 0.0 {
+            return;
+        }
+
+        let shift = direction.normalize() * self.move_speed * delta_time;
+        camera.set_center(&(camera.center() + shift));
+    }
+}
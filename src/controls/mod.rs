@@ -0,0 +1,11 @@
+mod controller;
+pub use controller::CameraController;
+
+mod orbit;
+pub use orbit::OrbitControls;
+
+mod pan_zoom;
+pub use pan_zoom::PanZoomControls;
+
+mod fly;
+pub use fly::FlyControls;
@@ -0,0 +1,124 @@
+use crate::{
+    controls::{
+        CameraController,
+        controller::{camera_basis, scroll_lines},
+    },
+    math::Camera,
+};
+use nalgebra::{Point3, UnitQuaternion, UnitVector3};
+use std::f32::consts::PI;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, WindowEvent},
+};
+
+// Keeps the camera from flipping over the poles
+const POLAR_EPSILON: f32 = 1e-3;
+
+pub struct OrbitControls {
+    pub target: Point3<f32>,
+    pub rotate_speed: f32,
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+
+    rotating: bool,
+    panning: bool,
+    cursor: Option<PhysicalPosition<f64>>,
+}
+
+impl OrbitControls {
+    pub fn new(target: Point3<f32>) -> Self {
+        Self {
+            target,
+            rotate_speed: 0.005,
+            pan_speed: 0.001,
+            zoom_speed: 1.1,
+            min_distance: 0.1,
+            max_distance: 1000.0,
+
+            rotating: false,
+            panning: false,
+            cursor: None,
+        }
+    }
+
+    pub fn orbit(&self, camera: &mut dyn Camera, delta_x: f32, delta_y: f32) {
+        let up = UnitVector3::new_normalize(*camera.up());
+        let offset = camera.center() - self.target.coords;
+
+        let offset = UnitQuaternion::from_axis_angle(&up, -delta_x * self.rotate_speed) * offset;
+
+        let polar = up.angle(&offset);
+        let target_polar =
+            (polar + delta_y * self.rotate_speed).clamp(POLAR_EPSILON, PI - POLAR_EPSILON);
+
+        let offset = match UnitVector3::try_new(up.cross(&offset), f32::EPSILON) {
+            Some(axis) => UnitQuaternion::from_axis_angle(&axis, target_polar - polar) * offset,
+            None => offset,
+        };
+
+        camera.set_center(&(self.target.coords + offset));
+        camera.look_at(&self.target);
+    }
+
+    pub fn pan(&mut self, camera: &mut dyn Camera, delta_x: f32, delta_y: f32) {
+        let (right, up, _) = camera_basis(camera);
+        let distance = (camera.center() - self.target.coords).norm();
+
+        let shift = (-right * delta_x + up * delta_y) * self.pan_speed * distance;
+
+        self.target += shift;
+        camera.set_center(&(camera.center() + shift));
+        camera.look_at(&self.target);
+    }
+
+    pub fn dolly(&self, camera: &mut dyn Camera, factor: f32) {
+        let offset = camera.center() - self.target.coords;
+        let distance = (offset.norm() * factor).clamp(self.min_distance, self.max_distance);
+
+        camera.set_center(&(self.target.coords + offset.normalize() * distance));
+        camera.look_at(&self.target);
+    }
+}
+
+impl CameraController for OrbitControls {
+    fn handle_event(&mut self, event: &WindowEvent, camera: &mut dyn Camera) -> bool {
+        match event {
+            WindowEvent::MouseInput { state, button, .. } => {
+                let pressed = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.rotating = pressed,
+                    MouseButton::Right | MouseButton::Middle => self.panning = pressed,
+                    _ => return false,
+                }
+
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let handled = self.rotating || self.panning;
+
+                if let Some(cursor) = self.cursor {
+                    let delta_x = (position.x - cursor.x) as f32;
+                    let delta_y = (position.y - cursor.y) as f32;
+
+                    if self.rotating {
+                        self.orbit(camera, delta_x, delta_y);
+                    } else if self.panning {
+                        self.pan(camera, delta_x, delta_y);
+                    }
+                }
+
+                self.cursor = Some(*position);
+
+                handled
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.dolly(camera, self.zoom_speed.powf(-scroll_lines(delta)));
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    controls::{
+        CameraController,
+        controller::{camera_basis, scroll_lines},
+    },
+    math::{Camera, OrthographicCamera},
+};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, WindowEvent},
+};
+
+// Meant for OrthographicCamera, other cameras are panned by `pan_speed` and never zoomed
+pub struct PanZoomControls {
+    pub pan_speed: f32,
+    pub zoom_speed: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+
+    dragging: bool,
+    cursor: Option<PhysicalPosition<f64>>,
+}
+
+impl PanZoomControls {
+    pub fn new() -> Self {
+        Self {
+            pan_speed: 0.01,
+            zoom_speed: 1.1,
+            min_zoom: 0.01,
+            max_zoom: 100.0,
+
+            dragging: false,
+            cursor: None,
+        }
+    }
+
+    pub fn pan(&self, camera: &mut dyn Camera, delta_x: f32, delta_y: f32) {
+        let (right, up, _) = camera_basis(camera);
+        let world_per_pixel = self.world_per_pixel(camera);
+
+        let shift = (-right * delta_x + up * delta_y) * world_per_pixel;
+        camera.set_center(&(camera.center() + shift));
+    }
+
+    // Zooms while keeping the world point under the cursor in place
+    pub fn zoom(&self, camera: &mut dyn Camera, factor: f32) {
+        let (right, up, _) = camera_basis(camera);
+        let world_per_pixel = self.world_per_pixel(camera);
+
+        let Some(orthographic) = camera.as_any_mut().downcast_mut::<OrthographicCamera>() else {
+            return;
+        };
+
+        let zoom = (orthographic.zoom() * factor).clamp(self.min_zoom, self.max_zoom);
+        let applied_factor = zoom / orthographic.zoom();
+        orthographic.set_zoom(zoom);
+
+        if let Some(cursor) = self.cursor {
+            let viewport = *orthographic.viewport();
+            let cursor_x = (cursor.x as f32 - viewport.x / 2.0) * world_per_pixel;
+            let cursor_y = (viewport.y / 2.0 - cursor.y as f32) * world_per_pixel;
+
+            let shift = (right * cursor_x + up * cursor_y) * (1.0 - 1.0 / applied_factor);
+            orthographic.set_center(&(orthographic.center() + shift));
+        }
+    }
+
+    fn world_per_pixel(&self, camera: &dyn Camera) -> f32 {
+        match camera.as_any().downcast_ref::<OrthographicCamera>() {
+            Some(orthographic) => {
+                orthographic.frame_height() / orthographic.zoom() / orthographic.viewport().y
+            }
+            None => self.pan_speed,
+        }
+    }
+}
+
+impl Default for PanZoomControls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraController for PanZoomControls {
+    fn handle_event(&mut self, event: &WindowEvent, camera: &mut dyn Camera) -> bool {
+        match event {
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left | MouseButton::Middle,
+                ..
+            } => {
+                self.dragging = *state == ElementState::Pressed;
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if let (true, Some(cursor)) = (self.dragging, self.cursor) {
+                    let delta_x = (position.x - cursor.x) as f32;
+                    let delta_y = (position.y - cursor.y) as f32;
+
+                    self.pan(camera, delta_x, delta_y);
+                }
+
+                self.cursor = Some(*position);
+
+                self.dragging
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.zoom(camera, self.zoom_speed.powf(scroll_lines(delta)));
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,33 +1,62 @@
 use crate::{
-    Scene,
-    animation::{Animation, LogicalTimeline, Timeline},
+    MeshHandle, PickCallback, PickResult, Scene,
+    animation::{Animation, LogicalTimeline, RateFunc, Timeline},
+    controls::CameraController,
     geometry::Mesh,
-    math::PerspectiveCamera,
-    render::Renderer,
+    math::Camera,
+    render::{Renderer, RendererError},
+};
+use nalgebra::Point2;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use winit::{
+    event::{ElementState, MouseButton, WindowEvent},
+    event_loop::EventLoop,
+    window::Window,
 };
-use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
-use winit::{event::WindowEvent, event_loop::EventLoop, window::Window};
 
 pub struct Canvas {
     pub window: Option<Arc<Window>>,
-    pub camera: PerspectiveCamera,
+    pub controller: Option<Box<dyn CameraController>>,
+    pub on_pick: Option<PickCallback>,
     pub renderer: Option<Renderer<'static>>,
     pub scene: Rc<RefCell<Scene>>,
 
     pub timeline: Rc<RefCell<Box<dyn Timeline>>>,
     pub playhead: f32,
+    pub default_rate_func: Option<RateFunc>,
+
+    sample_count: u32,
+    last_frame_time: Option<Instant>,
+    cursor: Option<Point2<f32>>,
+    pressed_at: HashMap<MouseButton, Point2<f32>>,
 }
 
+// How far, in pixels, the cursor may drift between press and release for a click to pick
+const CLICK_TOLERANCE: f32 = 4.0;
+
 impl Canvas {
     pub fn new() -> Self {
         Self {
             window: None,
-            camera: PerspectiveCamera::default(),
+            controller: None,
+            on_pick: None,
             renderer: None,
             scene: Rc::new(RefCell::new(Scene::new())),
 
             timeline: Rc::new(RefCell::new(Box::new(LogicalTimeline::new()))),
             playhead: 0.0,
+            default_rate_func: None,
+
+            sample_count: 1,
+            last_frame_time: None,
+            cursor: None,
+            pressed_at: HashMap::new(),
         }
     }
 
@@ -37,20 +66,86 @@ impl Canvas {
         event_loop.run_app(self).unwrap();
     }
 
-    pub fn add_mesh(&self, mesh: Mesh) -> usize {
+    pub fn set_camera<C: Camera + 'static>(&self, camera: C) {
+        self.scene.borrow_mut().set_camera(camera);
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    // Checked right away once the window is open, otherwise when the renderer is created
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<(), RendererError> {
+        if let Some(renderer) = self.renderer.as_mut() {
+            renderer.set_sample_count(sample_count)?;
+        }
+
+        self.sample_count = sample_count;
+        Ok(())
+    }
+
+    pub fn set_controller<C: CameraController + 'static>(&mut self, controller: C) {
+        self.controller = Some(Box::new(controller));
+    }
+
+    pub fn set_on_pick<F: FnMut(MouseButton, Option<PickResult>) + 'static>(
+        &mut self,
+        callback: F,
+    ) {
+        self.on_pick = Some(Box::new(callback));
+    }
+
+    pub fn add_mesh(&self, mesh: Mesh) -> MeshHandle {
         self.scene.borrow_mut().add_mesh(mesh)
     }
 
+    pub fn add_child(&self, parent: MeshHandle, mesh: Mesh) -> Option<MeshHandle> {
+        self.scene.borrow_mut().add_child(parent, mesh)
+    }
+
+    pub fn remove_mesh(&self, handle: MeshHandle) -> Option<Mesh> {
+        self.scene.borrow_mut().remove_mesh(handle)
+    }
+
+    pub fn replace_mesh(&self, handle: MeshHandle, mesh: Mesh) -> Option<Mesh> {
+        self.scene.borrow_mut().replace_mesh(handle, mesh)
+    }
+
     pub fn queue_animation<Ani: Animation>(&mut self, animation: Ani, duration: &Duration) {
         let mut action = animation.into_action(self.scene.clone());
         action.duration = duration.as_secs_f32();
         action.start_time = self.playhead;
 
+        // Animations wrapped with their own rate function keep it
+        if action.rate_func.is_none() {
+            action.rate_func = self.default_rate_func.clone();
+        }
+
         self.playhead += action.duration;
 
         self.timeline.borrow_mut().add_action(action);
     }
 
+    pub fn seek(&self, time: f32) {
+        self.timeline.borrow_mut().seek(time);
+    }
+
+    // Queued actions move over, rewound first so they replay from the new timeline's start
+    pub fn set_timeline<T: Timeline + 'static>(&self, mut timeline: T) {
+        let mut previous = self.timeline.borrow_mut();
+        previous.reset();
+
+        for action in previous.take_actions() {
+            timeline.add_action(action);
+        }
+
+        *previous = Box::new(timeline);
+    }
+
+    pub fn set_default_rate_func<F: Fn(f32) -> f32 + 'static>(&mut self, rate_func: F) {
+        self.default_rate_func = Some(Rc::new(rate_func));
+    }
+
     pub fn advance_playhead(&mut self, step: &Duration) {
         self.playhead += step.as_secs_f32();
     }
@@ -100,7 +195,14 @@ impl winit::application::ApplicationHandler for Canvas {
                 .await
                 .unwrap();
 
-            self.renderer = Some(Renderer::new(surface, device, queue, &adapter));
+            let mut renderer = Renderer::new(surface, device, queue, &adapter);
+
+            // The window's adapter may not support the requested count, fall back to none
+            if renderer.set_sample_count(self.sample_count).is_err() {
+                self.sample_count = renderer.sample_count();
+            }
+
+            self.renderer = Some(renderer);
         });
     }
 
@@ -110,26 +212,72 @@ impl winit::application::ApplicationHandler for Canvas {
         _window_id: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
+        if let Some(controller) = self.controller.as_mut() {
+            controller.handle_event(&event, self.scene.borrow_mut().camera_mut());
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
             WindowEvent::Resized(size) => {
-                self.camera
-                    .set_aspect(size.width as f32 / size.height as f32);
+                self.scene
+                    .borrow_mut()
+                    .camera_mut()
+                    .set_viewport(size.width as f32, size.height as f32);
 
                 self.renderer
                     .as_mut()
                     .unwrap()
                     .resize(size.width, size.height);
             }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor = Some(Point2::new(position.x as f32, position.y as f32));
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => {
+                if let Some(cursor) = self.cursor {
+                    self.pressed_at.insert(button, cursor);
+                }
+            }
+            // Picks on release, so drags that orbit or pan the camera do not pick
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button,
+                ..
+            } => {
+                let click = self.pressed_at.remove(&button).zip(self.cursor).filter(
+                    |(pressed_at, cursor)| {
+                        nalgebra::distance(pressed_at, cursor) <= CLICK_TOLERANCE
+                    },
+                );
+
+                if let (Some((_, cursor)), Some(on_pick)) = (click, self.on_pick.as_mut()) {
+                    // The scene borrow ends before the callback, which may well touch the scene
+                    let result = self.scene.borrow_mut().pick_screen(&cursor);
+                    on_pick(button, result);
+                }
+            }
             WindowEvent::RedrawRequested => {
+                let now = Instant::now();
+                let delta_time = self
+                    .last_frame_time
+                    .map_or(0.0, |last| (now - last).as_secs_f32());
+                self.last_frame_time = Some(now);
+
+                if let Some(controller) = self.controller.as_mut() {
+                    controller.update(self.scene.borrow_mut().camera_mut(), delta_time);
+                }
+
                 self.timeline.borrow_mut().forward();
 
                 self.renderer
                     .as_mut()
                     .unwrap()
-                    .render(&mut self.scene.borrow_mut(), &self.camera)
+                    .render(&mut self.scene.borrow_mut())
                     .unwrap();
 
                 self.window.as_ref().unwrap().request_redraw();
@@ -138,3 +286,36 @@ impl winit::application::ApplicationHandler for Canvas {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{Action, ManualClock, PhysicalTimeline};
+    use std::cell::Cell;
+
+    #[test]
+    fn replacing_the_timeline_keeps_queued_actions() {
+        let canvas = Canvas::new();
+        let progress = Rc::new(Cell::new(-1.0));
+        let progress_clone = progress.clone();
+
+        let mut action = Action::new();
+        action.on_update = Box::new(move |p, _| progress_clone.set(p));
+        canvas.timeline.borrow_mut().add_action(action);
+        canvas.seek(0.5);
+        assert_eq!(progress.get(), 0.5);
+
+        let clock = ManualClock::new();
+        canvas.set_timeline(PhysicalTimeline::with_clock(clock.clone()));
+        assert_eq!(progress.get(), 0.0);
+
+        let mut timeline = canvas.timeline.borrow_mut();
+        assert_eq!(timeline.actions().len(), 1);
+        assert_eq!(timeline.stop_time(), 1.0);
+
+        timeline.start();
+        clock.advance(0.25);
+        timeline.forward();
+        assert_eq!(progress.get(), 0.25);
+    }
+}
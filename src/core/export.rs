@@ -0,0 +1,264 @@
+use crate::{
+    Canvas,
+    animation::TimelineState,
+    render::{FrameImage, Renderer, RendererError},
+};
+use std::{
+    io::{ErrorKind, Read, Write},
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+    thread,
+};
+
+#[derive(Debug)]
+pub enum ExportError {
+    Renderer(RendererError),
+    Surface(wgpu::SurfaceError),
+    Io(std::io::Error),
+    Encoding(png::EncodingError),
+    // Carries whatever ffmpeg printed to stderr
+    FfmpegFailed(ExitStatus, String),
+    // A frame couldn't be rendered or written while encoding, along with ffmpeg's stderr
+    VideoFrameFailed(Box<ExportError>, String),
+}
+
+impl From<RendererError> for ExportError {
+    fn from(err: RendererError) -> Self {
+        Self::Renderer(err)
+    }
+}
+
+impl From<wgpu::SurfaceError> for ExportError {
+    fn from(err: wgpu::SurfaceError) -> Self {
+        Self::Surface(err)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<png::EncodingError> for ExportError {
+    fn from(err: png::EncodingError) -> Self {
+        Self::Encoding(err)
+    }
+}
+
+impl Canvas {
+    pub fn render_to_directory<P: AsRef<Path>>(
+        &mut self,
+        dir: P,
+        width: u32,
+        height: u32,
+    ) -> Result<u32, ExportError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        self.render_frames(width, height, |index, frame| {
+            frame.save_png(dir.join(format!("frame_{:05}.png", index)))?;
+            Ok(())
+        })
+    }
+
+    pub fn render_to_video<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        width: u32,
+        height: u32,
+    ) -> Result<u32, ExportError> {
+        let fps = self.timeline.borrow().logical_fps();
+
+        // ffmpeg must be available in PATH, the codec is picked from the extension of `path`
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pixel_format", "rgba"])
+            .args(["-video_size", &format!("{}x{}", width, height)])
+            .args(["-framerate", &fps.to_string()])
+            .args(["-i", "-", "-pix_fmt", "yuv420p"])
+            .arg(path.as_ref())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // SAFETY: stdin and stderr were both set to Stdio::piped() above
+        let mut stdin = ffmpeg.stdin.take().unwrap();
+        let mut stderr = ffmpeg.stderr.take().unwrap();
+
+        // Drained on its own thread, a full stderr pipe would otherwise block ffmpeg and then us
+        let stderr_reader = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            output
+        });
+
+        let frame_count = self.render_frames(width, height, |_, frame| {
+            stdin.write_all(&frame.data)?;
+            Ok(())
+        });
+
+        // Closing stdin signals the end of the stream to ffmpeg
+        drop(stdin);
+        let status = ffmpeg.wait()?;
+        let stderr_output = stderr_reader.join().unwrap_or_default();
+
+        match frame_count {
+            // A failing ffmpeg breaks the pipe, so its status explains the write error best
+            Err(ExportError::Io(err))
+                if err.kind() == ErrorKind::BrokenPipe && !status.success() =>
+            {
+                Err(ExportError::FfmpegFailed(status, stderr_output))
+            }
+            Err(err) => Err(ExportError::VideoFrameFailed(Box::new(err), stderr_output)),
+            Ok(_) if !status.success() => Err(ExportError::FfmpegFailed(status, stderr_output)),
+            Ok(frame_count) => Ok(frame_count),
+        }
+    }
+
+    fn render_frames<F: FnMut(u32, FrameImage) -> Result<(), ExportError>>(
+        &mut self,
+        width: u32,
+        height: u32,
+        on_frame: F,
+    ) -> Result<u32, ExportError> {
+        let mut renderer = Renderer::new_headless(width, height)?;
+        // The headless adapter may not support the canvas's count, fall back to none like the
+        // window does
+        let _ = renderer.set_sample_count(self.sample_count());
+
+        // Exporting leaves the camera and the timeline where they were, playing or not
+        let viewport = {
+            let mut scene = self.scene.borrow_mut();
+            let viewport = *scene.camera().viewport();
+            scene.camera_mut().set_viewport(width as f32, height as f32);
+            viewport
+        };
+        let (time, state) = {
+            let timeline = self.timeline.borrow();
+            (timeline.current_time(), *timeline.state())
+        };
+
+        let frame_count = self.seek_frames(&mut renderer, on_frame);
+
+        let mut timeline = self.timeline.borrow_mut();
+        match state {
+            TimelineState::WAITING => {
+                timeline.reset();
+                timeline.seek(time);
+            }
+            TimelineState::PLAYING => {
+                timeline.seek(time);
+                timeline.resume();
+            }
+            TimelineState::PAUSED => timeline.seek(time),
+        }
+
+        self.scene
+            .borrow_mut()
+            .camera_mut()
+            .set_viewport(viewport.x, viewport.y);
+
+        frame_count
+    }
+
+    fn seek_frames<F: FnMut(u32, FrameImage) -> Result<(), ExportError>>(
+        &mut self,
+        renderer: &mut Renderer,
+        mut on_frame: F,
+    ) -> Result<u32, ExportError> {
+        // Every frame is placed by seeking, which keeps wall-clock timelines deterministic too
+        let (start_time, fps) = {
+            let mut timeline = self.timeline.borrow_mut();
+            timeline.reset();
+            timeline.pause();
+            (timeline.start_time(), timeline.logical_fps())
+        };
+
+        let mut frame_index = 0;
+        loop {
+            let time = start_time + frame_index as f32 / fps;
+            self.timeline.borrow_mut().seek(time);
+
+            renderer.render(&mut self.scene.borrow_mut())?;
+            on_frame(frame_index, renderer.read_frame()?)?;
+            frame_index += 1;
+
+            if time >= self.timeline.borrow().stop_time() {
+                break;
+            }
+        }
+
+        Ok(frame_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::{Action, ManualClock, PhysicalTimeline};
+    use std::{cell::Cell, rc::Rc};
+
+    #[test]
+    fn exporting_puts_the_camera_and_timeline_back_where_they_were() {
+        let mut canvas = Canvas::new();
+        canvas
+            .scene
+            .borrow_mut()
+            .camera_mut()
+            .set_viewport(640.0, 480.0);
+
+        let clock = ManualClock::new();
+        canvas.set_timeline(PhysicalTimeline::with_clock(clock.clone()));
+
+        let progress = Rc::new(Cell::new(-1.0));
+        let progress_clone = progress.clone();
+        let mut action = Action::new();
+        action.duration = 0.1;
+        action.on_update = Box::new(move |p, _| progress_clone.set(p));
+        canvas.timeline.borrow_mut().add_action(action);
+
+        canvas.timeline.borrow_mut().start();
+        clock.advance(0.05);
+        canvas.timeline.borrow_mut().forward();
+
+        let dir = std::env::temp_dir().join(format!("mraphics_export_{}", std::process::id()));
+        let result = canvas.render_to_directory(&dir, 8, 8);
+        let _ = std::fs::remove_dir_all(&dir);
+        if let Err(ExportError::Renderer(_)) = result {
+            eprintln!("skipped, no adapter available");
+            return;
+        }
+        assert!(result.is_ok());
+
+        let viewport = *canvas.scene.borrow().camera().viewport();
+        assert_eq!(viewport, nalgebra::Vector2::new(640.0, 480.0));
+
+        let mut timeline = canvas.timeline.borrow_mut();
+        assert!(matches!(timeline.state(), TimelineState::PLAYING));
+        assert!((timeline.current_time() - 0.05).abs() < 1e-4);
+        assert!((progress.get() - 0.5).abs() < 1e-3);
+
+        clock.advance(0.025);
+        timeline.forward();
+        assert!((progress.get() - 0.75).abs() < 1e-3);
+    }
+
+    #[test]
+    fn exporting_falls_back_to_no_multisampling() {
+        let mut canvas = Canvas::new();
+        canvas.set_sample_count(3).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("mraphics_msaa_{}", std::process::id()));
+        let result = canvas.render_to_directory(&dir, 8, 8);
+        let _ = std::fs::remove_dir_all(&dir);
+        if let Err(ExportError::Renderer(RendererError::AdapterUnavailable(_))) = result {
+            eprintln!("skipped, no adapter available");
+            return;
+        }
+
+        assert!(result.is_ok());
+        assert_eq!(canvas.sample_count(), 3);
+    }
+}
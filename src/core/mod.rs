@@ -2,4 +2,10 @@ mod canvas;
 pub use canvas::*;
 
 mod scene;
-pub use scene::Scene;
+pub use scene::{MeshHandle, Scene};
+
+mod picking;
+pub use picking::{PickCallback, PickResult};
+
+mod export;
+pub use export::ExportError;
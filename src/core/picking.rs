@@ -0,0 +1,76 @@
+use crate::{MeshHandle, Scene, math::Ray};
+use nalgebra::{Point2, Point3};
+use winit::event::MouseButton;
+
+pub type PickCallback = Box<dyn FnMut(MouseButton, Option<PickResult>)>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PickResult {
+    pub mesh: MeshHandle,
+    pub distance: f32,
+    pub point: Point3<f32>,
+}
+
+impl Scene {
+    // Closest mesh whose triangles the ray hits, instanced meshes are tested per instance
+    pub fn pick(&mut self, ray: &Ray) -> Option<PickResult> {
+        self.update_world_matrices();
+
+        let mut closest: Option<PickResult> = None;
+
+        for handle in self.handles_depth_first() {
+            // SAFETY: Every reachable slot is occupied
+            let mesh = self.get_mesh(handle).unwrap();
+
+            let Some(distance_to_bounds) = mesh
+                .world_bounding_box()
+                .and_then(|bounds| bounds.intersect_ray(ray))
+            else {
+                continue;
+            };
+            if closest.is_some_and(|closest| closest.distance < distance_to_bounds) {
+                continue;
+            }
+
+            let triangles = mesh.geometry.triangles();
+            if triangles.is_empty() {
+                continue;
+            }
+            let positions = mesh.geometry.positions();
+
+            for transform in mesh.world_transforms() {
+                let world_positions: Vec<Point3<f32>> = positions
+                    .iter()
+                    .map(|position| transform.transform_point(position))
+                    .collect();
+
+                for triangle in &triangles {
+                    let [Some(a), Some(b), Some(c)] =
+                        triangle.map(|vertex| world_positions.get(vertex as usize))
+                    else {
+                        continue;
+                    };
+
+                    let Some(distance) = ray.intersect_triangle(a, b, c) else {
+                        continue;
+                    };
+
+                    if closest.is_none_or(|closest| distance < closest.distance) {
+                        closest = Some(PickResult {
+                            mesh: handle,
+                            distance,
+                            point: ray.point_at(distance),
+                        });
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    pub fn pick_screen(&mut self, screen: &Point2<f32>) -> Option<PickResult> {
+        let ray = self.camera().screen_to_ray(screen)?;
+        self.pick(&ray)
+    }
+}
@@ -1,45 +1,412 @@
-use crate::geometry::Mesh;
-use std::{
-    collections::HashMap,
-    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+use crate::{
+    geometry::Mesh,
+    math::{Aabb, BoundingSphere, Camera, PerspectiveCamera},
 };
+use nalgebra::Matrix4;
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
 
-static GLOBAL_MESH_ID: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_SCENE_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Only resolves in the scene that created it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshHandle {
+    scene: usize,
+    index: u32,
+    generation: u32,
+}
+
+struct MeshSlot {
+    generation: u32,
+    mesh: Option<Mesh>,
+    parent: Option<MeshHandle>,
+    children: Vec<MeshHandle>,
+}
 
 pub struct Scene {
-    pub meshes: HashMap<usize, Mesh>,
+    id: usize,
+    slots: Vec<MeshSlot>,
+    free_slots: Vec<u32>,
+    roots: Vec<MeshHandle>,
+    released_identifiers: Vec<String>,
+
+    camera: Box<dyn Camera>,
 }
 
 impl Scene {
     pub fn new() -> Self {
         Self {
-            meshes: HashMap::new(),
+            id: GLOBAL_SCENE_ID.fetch_add(1, Relaxed),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            roots: Vec::new(),
+            released_identifiers: Vec::new(),
+
+            camera: Box::new(PerspectiveCamera::default()),
+        }
+    }
+
+    pub fn camera(&self) -> &dyn Camera {
+        self.camera.as_ref()
+    }
+
+    pub fn camera_mut(&mut self) -> &mut dyn Camera {
+        self.camera.as_mut()
+    }
+
+    pub fn set_camera<C: Camera + 'static>(&mut self, camera: C) {
+        self.camera = Box::new(camera);
+    }
+
+    pub fn add_mesh(&mut self, mesh: Mesh) -> MeshHandle {
+        let handle = self.insert(mesh, None);
+        self.roots.push(handle);
+        handle
+    }
+
+    pub fn add_child(&mut self, parent: MeshHandle, mesh: Mesh) -> Option<MeshHandle> {
+        if !self.contains(parent) {
+            return None;
         }
+
+        let handle = self.insert(mesh, Some(parent));
+        self.slots[parent.index as usize].children.push(handle);
+
+        Some(handle)
     }
 
-    pub fn add_mesh(&mut self, mesh: Mesh) -> usize {
-        self.meshes
-            .insert(GLOBAL_MESH_ID.fetch_add(1, Relaxed), mesh);
-        self.meshes.len() - 1
+    pub fn remove_mesh(&mut self, handle: MeshHandle) -> Option<Mesh> {
+        if !self.contains(handle) {
+            return None;
+        }
+
+        self.siblings_mut(handle)
+            .retain(|sibling| *sibling != handle);
+
+        Some(self.take(handle))
     }
 
-    pub fn get_mesh(&self, index: usize) -> &Mesh {
-        self.meshes.get(&index).unwrap()
+    pub fn replace_mesh(&mut self, handle: MeshHandle, mut mesh: Mesh) -> Option<Mesh> {
+        if !self.contains(handle) {
+            return None;
+        }
+
+        let children = std::mem::take(&mut mesh.children);
+        mesh.invalidate_world_matrix();
+
+        // SAFETY: Checked upon
+        let mut old_mesh = self.slots[handle.index as usize]
+            .mesh
+            .replace(mesh)
+            .unwrap();
+        self.release(&mut old_mesh);
+
+        for child in children {
+            let child_handle = self.insert(child, Some(handle));
+            self.slots[handle.index as usize]
+                .children
+                .push(child_handle);
+        }
+
+        Some(old_mesh)
     }
 
-    pub fn get_mesh_mut(&mut self, index: usize) -> &mut Mesh {
-        self.meshes.get_mut(&index).unwrap()
+    pub fn bring_to_front(&mut self, handle: MeshHandle) {
+        if !self.contains(handle) {
+            return;
+        }
+
+        let siblings = self.siblings_mut(handle);
+        siblings.retain(|sibling| *sibling != handle);
+        siblings.push(handle);
+    }
+
+    pub fn send_to_back(&mut self, handle: MeshHandle) {
+        if !self.contains(handle) {
+            return;
+        }
+
+        let siblings = self.siblings_mut(handle);
+        siblings.retain(|sibling| *sibling != handle);
+        siblings.insert(0, handle);
+    }
+
+    pub fn take_released_identifiers(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.released_identifiers)
+    }
+
+    pub fn contains(&self, handle: MeshHandle) -> bool {
+        self.slot(handle).is_some()
+    }
+
+    pub fn get_mesh(&self, handle: MeshHandle) -> Option<&Mesh> {
+        self.slot(handle).and_then(|slot| slot.mesh.as_ref())
+    }
+
+    pub fn get_mesh_mut(&mut self, handle: MeshHandle) -> Option<&mut Mesh> {
+        self.slot_mut(handle).and_then(|slot| slot.mesh.as_mut())
+    }
+
+    pub fn parent(&self, handle: MeshHandle) -> Option<MeshHandle> {
+        self.slot(handle).and_then(|slot| slot.parent)
+    }
+
+    pub fn children(&self, handle: MeshHandle) -> &[MeshHandle] {
+        self.slot(handle).map_or(&[], |slot| &slot.children)
+    }
+
+    pub fn roots(&self) -> &[MeshHandle] {
+        &self.roots
+    }
+
+    // Bounds of the mesh together with all its descendants
+    pub fn world_bounding_box(&mut self, handle: MeshHandle) -> Option<Aabb> {
+        self.update_world_matrices();
+
+        self.subtree(handle)
+            .into_iter()
+            .filter_map(|handle| self.get_mesh(handle)?.world_bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn world_bounding_sphere(&mut self, handle: MeshHandle) -> Option<BoundingSphere> {
+        self.update_world_matrices();
+
+        self.subtree(handle)
+            .into_iter()
+            .filter_map(|handle| self.get_mesh(handle)?.world_bounding_sphere())
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn update_world_matrices(&mut self) {
+        let mut stack: Vec<(MeshHandle, Option<Matrix4<f32>>, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|handle| (*handle, None, false))
+            .collect();
+
+        while let Some((handle, parent_world_matrix, force)) = stack.pop() {
+            let slot = &mut self.slots[handle.index as usize];
+
+            // SAFETY: Every reachable slot is occupied
+            let mesh = slot.mesh.as_mut().unwrap();
+            let updated = mesh.update_world_matrix(parent_world_matrix.as_ref(), force);
+            let world_matrix = *mesh.world_matrix();
+
+            for child in slot.children.iter().rev() {
+                stack.push((*child, Some(world_matrix), updated));
+            }
+        }
     }
 
     pub fn traverse<F: Fn(&Mesh)>(&self, callback: &F) {
-        for (_, mesh) in &self.meshes {
-            mesh.traverse(callback);
+        for handle in self.handles_depth_first() {
+            // SAFETY: Every reachable slot is occupied
+            callback(self.get_mesh(handle).unwrap());
         }
     }
 
     pub fn traverse_mut<F: FnMut(&mut Mesh)>(&mut self, callback: &mut F) {
-        for (_, mesh) in &mut self.meshes {
-            mesh.traverse_mut(callback);
+        for handle in self.handles_depth_first() {
+            // SAFETY: Every reachable slot is occupied
+            callback(self.get_mesh_mut(handle).unwrap());
         }
     }
+
+    pub fn handles_depth_first(&self) -> Vec<MeshHandle> {
+        self.depth_first(self.roots.iter().rev().copied().collect())
+    }
+
+    fn subtree(&self, handle: MeshHandle) -> Vec<MeshHandle> {
+        if !self.contains(handle) {
+            return Vec::new();
+        }
+
+        self.depth_first(vec![handle])
+    }
+
+    fn depth_first(&self, mut stack: Vec<MeshHandle>) -> Vec<MeshHandle> {
+        let mut out = Vec::new();
+
+        while let Some(handle) = stack.pop() {
+            out.push(handle);
+            stack.extend(self.children(handle).iter().rev());
+        }
+
+        out
+    }
+
+    fn insert(&mut self, mut mesh: Mesh, parent: Option<MeshHandle>) -> MeshHandle {
+        // Children built with Mesh::with_child get their own slots
+        let children = std::mem::take(&mut mesh.children);
+        mesh.invalidate_world_matrix();
+
+        let handle = match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.mesh = Some(mesh);
+                slot.parent = parent;
+
+                MeshHandle {
+                    scene: self.id,
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(MeshSlot {
+                    generation: 0,
+                    mesh: Some(mesh),
+                    parent,
+                    children: Vec::new(),
+                });
+
+                MeshHandle {
+                    scene: self.id,
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+
+        for child in children {
+            let child_handle = self.insert(child, Some(handle));
+            self.slots[handle.index as usize]
+                .children
+                .push(child_handle);
+        }
+
+        handle
+    }
+
+    fn take(&mut self, handle: MeshHandle) -> Mesh {
+        let slot = &mut self.slots[handle.index as usize];
+
+        // SAFETY: Only called on occupied slots
+        let mut mesh = slot.mesh.take().unwrap();
+        let children = std::mem::take(&mut slot.children);
+
+        slot.parent = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+
+        self.release(&mut mesh);
+
+        // Hand the subtree back the way Mesh::with_child built it
+        for child in children {
+            let child_mesh = self.take(child);
+            mesh.children.push(child_mesh);
+        }
+
+        mesh
+    }
+
+    fn release(&mut self, mesh: &mut Mesh) {
+        self.released_identifiers
+            .push(mesh.geometry.identifier().to_string());
+
+        if let Some(instances) = &mesh.instances {
+            self.released_identifiers
+                .push(instances.identifier().to_string());
+        }
+
+        mesh.invalidate_buffers();
+    }
+
+    fn siblings_mut(&mut self, handle: MeshHandle) -> &mut Vec<MeshHandle> {
+        match self.parent(handle) {
+            Some(parent) => &mut self.slots[parent.index as usize].children,
+            None => &mut self.roots,
+        }
+    }
+
+    fn slot(&self, handle: MeshHandle) -> Option<&MeshSlot> {
+        if handle.scene != self.id {
+            return None;
+        }
+
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.mesh.is_some())
+    }
+
+    fn slot_mut(&mut self, handle: MeshHandle) -> Option<&mut MeshSlot> {
+        if handle.scene != self.id {
+            return None;
+        }
+
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation && slot.mesh.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{Cube, CubeDescriptor},
+        material::BasicMaterial,
+    };
+    use nalgebra::{Point3, Vector3};
+
+    fn cube() -> Mesh {
+        Mesh::new(Cube::new(&CubeDescriptor::default()), BasicMaterial {})
+    }
+
+    #[test]
+    fn handles_do_not_resolve_in_other_scenes() {
+        let mut first = Scene::new();
+        let mut second = Scene::new();
+
+        let handle = first.add_mesh(cube());
+        second.add_mesh(cube());
+
+        assert!(first.contains(handle));
+        assert!(!second.contains(handle));
+        assert!(second.get_mesh_mut(handle).is_none());
+        assert!(second.remove_mesh(handle).is_none());
+    }
+
+    #[test]
+    fn stale_handles_do_not_resolve_after_removal() {
+        let mut scene = Scene::new();
+
+        let handle = scene.add_mesh(cube());
+        scene.remove_mesh(handle);
+        let reused = scene.add_mesh(cube());
+
+        assert!(scene.get_mesh(handle).is_none());
+        assert!(scene.get_mesh(reused).is_some());
+    }
+
+    #[test]
+    fn prebuilt_children_get_their_own_slots() {
+        let mut scene = Scene::new();
+
+        let parent = scene.add_mesh(cube().with_child(cube()).with_child(cube()));
+
+        assert_eq!(scene.children(parent).len(), 2);
+        assert!(scene.get_mesh(parent).unwrap().children().is_empty());
+
+        let removed = scene.remove_mesh(parent).unwrap();
+        assert_eq!(removed.children().len(), 2);
+    }
+
+    #[test]
+    fn world_bounds_merge_the_subtree_in_world_space() {
+        let local = cube().geometry.bounding_box().unwrap();
+
+        let mut child = cube();
+        child.set_position(&Point3::new(0.0, 3.0, 0.0));
+        let mut parent = cube().with_child(child);
+        parent.set_position(&Point3::new(2.0, 0.0, 0.0));
+
+        let mut scene = Scene::new();
+        let handle = scene.add_mesh(parent);
+        let bounds = scene.world_bounding_box(handle).unwrap();
+
+        assert_eq!(bounds.min, local.min + Vector3::new(2.0, 0.0, 0.0));
+        assert_eq!(bounds.max, local.max + Vector3::new(2.0, 3.0, 0.0));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    geometry::{Attribute, Geometry, GeometryView},
+    geometry::{Attribute, Geometry, GeometryView, IndexData, Indices},
     impl_inner_geometry_view,
 };
 use nalgebra::Vector3;
@@ -36,6 +36,7 @@ impl Cube {
 
         let mut vertices: Vec<f32> = Vec::new();
         let mut colors: Vec<f32> = Vec::new();
+        let mut indices: Vec<u16> = Vec::new();
 
         let mut build_plane =
             |position: Vector3<f32>, width_len: f32, height_len: f32, normal: Vector3<f32>| {
@@ -45,19 +46,19 @@ impl Cube {
                 let mut width = height.cross(&normal);
                 width.set_magnitude(width_len);
 
+                let base_index = (vertices.len() / 3) as u16;
+
                 vertices.extend(position.iter());
                 vertices.extend((position + width).iter());
                 vertices.extend((position + width + height).iter());
                 vertices.extend((position + height).iter());
-                vertices.extend(position.iter());
-                vertices.extend((position + width + height).iter());
 
                 colors.extend(desc.color.iter());
                 colors.extend(desc.color.iter());
                 colors.extend(desc.color.iter());
                 colors.extend(desc.color.iter());
-                colors.extend(desc.color.iter());
-                colors.extend(desc.color.iter());
+
+                indices.extend([0, 1, 2, 3, 0, 2].map(|offset| base_index + offset));
             };
 
         let w = desc.width;
@@ -117,6 +118,8 @@ impl Cube {
             needs_update_buffer: true,
         });
 
+        *out.indices_mut() = Some(Indices::new(IndexData::U16(indices)));
+
         out
     }
 }
@@ -1,5 +1,11 @@
-use crate::render::GadgetIndex;
+use crate::{
+    constants::POSITION_ATTR_LABEL,
+    math::{Aabb, BoundingSphere},
+    render::GadgetIndex,
+};
+use nalgebra::Point3;
 use std::{
+    cell::Cell,
     fmt::Debug,
     sync::atomic::{AtomicUsize, Ordering::Relaxed},
 };
@@ -13,11 +19,129 @@ pub struct Attribute {
     pub needs_update_buffer: bool,
 }
 
+#[derive(Clone, Debug)]
+pub enum IndexData {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl IndexData {
+    pub fn len(&self) -> u32 {
+        match self {
+            IndexData::U16(data) => data.len() as u32,
+            IndexData::U32(data) => data.len() as u32,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            IndexData::U16(_) => wgpu::IndexFormat::Uint16,
+            IndexData::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            IndexData::U16(data) => bytemuck::cast_slice(data),
+            IndexData::U32(data) => bytemuck::cast_slice(data),
+        }
+    }
+
+    pub fn to_u32_vec(&self) -> Vec<u32> {
+        match self {
+            IndexData::U16(data) => data.iter().map(|index| *index as u32).collect(),
+            IndexData::U32(data) => data.clone(),
+        }
+    }
+
+    // Only strips restart, in lists the same value is an ordinary vertex index
+    pub fn primitive_restart(&self) -> u32 {
+        match self {
+            IndexData::U16(_) => u16::MAX as u32,
+            IndexData::U32(_) => u32::MAX,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Indices {
+    pub data: IndexData,
+    pub needs_update_value: bool,
+    pub needs_update_buffer: bool,
+}
+
+impl Indices {
+    pub fn new(data: IndexData) -> Self {
+        Self {
+            data,
+            needs_update_value: true,
+            needs_update_buffer: true,
+        }
+    }
+}
+
 pub trait GeometryView {
     fn attributes(&self) -> &Vec<Attribute>;
     fn attributes_mut(&mut self) -> &mut Vec<Attribute>;
-    fn indices(&self) -> u32;
+    fn indices(&self) -> Option<&Indices>;
+    fn indices_mut(&mut self) -> &mut Option<Indices>;
+    fn vertex_count(&self) -> u32;
     fn identifier(&self) -> &str;
+    fn topology(&self) -> wgpu::PrimitiveTopology;
+
+    fn bounding_box(&self) -> Option<Aabb>;
+    fn bounding_sphere(&self) -> Option<BoundingSphere>;
+    fn invalidate_bounds(&self);
+
+    fn positions(&self) -> Vec<Point3<f32>> {
+        let Some(position) = self
+            .attributes()
+            .iter()
+            .find(|attr| attr.label == POSITION_ATTR_LABEL)
+        else {
+            return Vec::new();
+        };
+
+        position
+            .data
+            .chunks_exact(3 * 4)
+            .map(|vertex| {
+                let coordinate =
+                    |i: usize| f32::from_ne_bytes(vertex[i * 4..i * 4 + 4].try_into().unwrap());
+                Point3::new(coordinate(0), coordinate(1), coordinate(2))
+            })
+            .collect()
+    }
+
+    // Vertex indices of every triangle drawn, empty for line and point topologies
+    fn triangles(&self) -> Vec<[u32; 3]> {
+        let vertices = match self.indices() {
+            Some(indices) => indices.data.to_u32_vec(),
+            None => (0..self.vertex_count()).collect(),
+        };
+
+        // Indexed strips are drawn with strip_index_format set, see PipelineKey::from_mesh
+        let restart = self
+            .indices()
+            .map(|indices| indices.data.primitive_restart());
+
+        match self.topology() {
+            wgpu::PrimitiveTopology::TriangleList => vertices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect(),
+            wgpu::PrimitiveTopology::TriangleStrip => vertices
+                .windows(3)
+                .filter(|triangle| restart.is_none_or(|restart| !triangle.contains(&restart)))
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 static GLOBAL_GEOMETRY_ID: AtomicUsize = AtomicUsize::new(0);
@@ -25,25 +149,44 @@ const GEOMETRY_IDENTIFIER_PREFIX: &'static str = "mraphics-geometry-";
 
 pub struct Geometry {
     pub attributes: Vec<Attribute>,
+    pub indices: Option<Indices>,
+    pub topology: wgpu::PrimitiveTopology,
 
     identifier: String,
+    bounding_box: Cell<Option<Aabb>>,
+    bounding_sphere: Cell<Option<BoundingSphere>>,
 }
 
 impl Geometry {
     pub fn new() -> Self {
         Self {
             attributes: Vec::new(),
+            indices: None,
+            topology: wgpu::PrimitiveTopology::TriangleList,
             identifier: String::from(GEOMETRY_IDENTIFIER_PREFIX)
                 + &GLOBAL_GEOMETRY_ID.fetch_add(1, Relaxed).to_string(),
+            bounding_box: Cell::new(None),
+            bounding_sphere: Cell::new(None),
         }
     }
 
     pub fn with_id_prefix(prefix: String) -> Self {
         Self {
             attributes: Vec::new(),
+            indices: None,
+            topology: wgpu::PrimitiveTopology::TriangleList,
             identifier: prefix + &GLOBAL_GEOMETRY_ID.fetch_add(1, Relaxed).to_string(),
+            bounding_box: Cell::new(None),
+            bounding_sphere: Cell::new(None),
         }
     }
+
+    // Edited positions are flagged with needs_update_value until the renderer uploads them
+    fn positions_changed(&self) -> bool {
+        self.attributes
+            .iter()
+            .any(|attr| attr.label == POSITION_ATTR_LABEL && attr.needs_update_value)
+    }
 }
 
 impl GeometryView for Geometry {
@@ -55,13 +198,59 @@ impl GeometryView for Geometry {
         &mut self.attributes
     }
 
-    fn indices(&self) -> u32 {
-        self.attributes[0].data.len() as u32 / 4
+    fn indices(&self) -> Option<&Indices> {
+        self.indices.as_ref()
+    }
+
+    fn indices_mut(&mut self) -> &mut Option<Indices> {
+        &mut self.indices
+    }
+
+    fn vertex_count(&self) -> u32 {
+        // Positions are packed as three f32 per vertex
+        self.attributes
+            .iter()
+            .find(|attr| attr.label == POSITION_ATTR_LABEL)
+            .map_or(0, |attr| (attr.data.len() / (3 * 4)) as u32)
     }
 
     fn identifier(&self) -> &str {
         &self.identifier
     }
+
+    fn topology(&self) -> wgpu::PrimitiveTopology {
+        self.topology
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.positions_changed() {
+            self.invalidate_bounds();
+        }
+
+        if self.bounding_box.get().is_none() {
+            self.bounding_box.set(Aabb::from_points(&self.positions()));
+        }
+
+        self.bounding_box.get()
+    }
+
+    fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        if self.positions_changed() {
+            self.invalidate_bounds();
+        }
+
+        if self.bounding_sphere.get().is_none() {
+            self.bounding_sphere
+                .set(BoundingSphere::from_points(&self.positions()));
+        }
+
+        self.bounding_sphere.get()
+    }
+
+    fn invalidate_bounds(&self) {
+        self.bounding_box.set(None);
+        self.bounding_sphere.set(None);
+    }
 }
 
 #[macro_export]
@@ -80,9 +269,65 @@ macro_rules! impl_inner_geometry_view {
                 self.inner.identifier()
             }
 
-            fn indices(&self) -> u32 {
+            fn indices(&self) -> Option<&$crate::geometry::Indices> {
                 self.inner.indices()
             }
+
+            fn indices_mut(&mut self) -> &mut Option<$crate::geometry::Indices> {
+                self.inner.indices_mut()
+            }
+
+            fn vertex_count(&self) -> u32 {
+                self.inner.vertex_count()
+            }
+
+            fn topology(&self) -> wgpu::PrimitiveTopology {
+                self.inner.topology()
+            }
+
+            fn bounding_box(&self) -> Option<$crate::math::Aabb> {
+                self.inner.bounding_box()
+            }
+
+            fn bounding_sphere(&self) -> Option<$crate::math::BoundingSphere> {
+                self.inner.bounding_sphere()
+            }
+
+            fn invalidate_bounds(&self) {
+                self.inner.invalidate_bounds()
+            }
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indexed(data: IndexData, topology: wgpu::PrimitiveTopology) -> Geometry {
+        let mut geometry = Geometry::new();
+        geometry.indices = Some(Indices::new(data));
+        geometry.topology = topology;
+        geometry
+    }
+
+    #[test]
+    fn max_u16_index_is_a_vertex_in_lists() {
+        let geometry = indexed(
+            IndexData::U16(vec![0, 1, u16::MAX]),
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        assert_eq!(geometry.triangles(), vec![[0, 1, u16::MAX as u32]]);
+    }
+
+    #[test]
+    fn strips_restart_on_the_max_index_of_their_format() {
+        let geometry = indexed(
+            IndexData::U16(vec![0, 1, 2, 3, u16::MAX, 4, 5, 6]),
+            wgpu::PrimitiveTopology::TriangleStrip,
+        );
+
+        assert_eq!(geometry.triangles(), vec![[0, 1, 2], [1, 2, 3], [4, 5, 6]]);
+    }
+}
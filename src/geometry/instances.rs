@@ -0,0 +1,94 @@
+use nalgebra::{Matrix4, Vector4};
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+static GLOBAL_INSTANCES_ID: AtomicUsize = AtomicUsize::new(0);
+const INSTANCES_IDENTIFIER_PREFIX: &str = "mraphics-instances-";
+
+pub struct Instances {
+    pub needs_update_value: bool,
+    pub needs_update_buffer: bool,
+
+    transforms: Vec<Matrix4<f32>>,
+    colors: Vec<Vector4<f32>>,
+    identifier: String,
+}
+
+impl Instances {
+    pub fn new() -> Self {
+        Self {
+            needs_update_value: true,
+            needs_update_buffer: true,
+
+            transforms: Vec::new(),
+            colors: Vec::new(),
+            identifier: String::from(INSTANCES_IDENTIFIER_PREFIX)
+                + &GLOBAL_INSTANCES_ID.fetch_add(1, Relaxed).to_string(),
+        }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub fn len(&self) -> usize {
+        self.transforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    pub fn push(&mut self, transform: Matrix4<f32>, color: Vector4<f32>) {
+        self.transforms.push(transform);
+        self.colors.push(color);
+
+        self.needs_update_buffer = true;
+        self.needs_update_value = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.transforms.clear();
+        self.colors.clear();
+
+        self.needs_update_buffer = true;
+        self.needs_update_value = true;
+    }
+
+    pub fn transform(&self, index: usize) -> &Matrix4<f32> {
+        &self.transforms[index]
+    }
+
+    pub fn set_transform(&mut self, index: usize, transform: Matrix4<f32>) {
+        self.transforms[index] = transform;
+        self.needs_update_value = true;
+    }
+
+    pub fn color(&self, index: usize) -> &Vector4<f32> {
+        &self.colors[index]
+    }
+
+    pub fn set_color(&mut self, index: usize, color: Vector4<f32>) {
+        self.colors[index] = color;
+        self.needs_update_value = true;
+    }
+
+    pub fn transform_data(&self) -> Vec<f32> {
+        self.transforms
+            .iter()
+            .flat_map(|transform| transform.iter().copied())
+            .collect()
+    }
+
+    pub fn color_data(&self) -> Vec<f32> {
+        self.colors
+            .iter()
+            .flat_map(|color| color.iter().copied())
+            .collect()
+    }
+}
+
+impl Default for Instances {
+    fn default() -> Self {
+        Self::new()
+    }
+}
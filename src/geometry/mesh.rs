@@ -1,51 +1,145 @@
-use crate::{geometry::GeometryView, material::Material};
-use nalgebra::{Isometry3, Matrix4, Translation3, UnitQuaternion, UnitVector3, Vector3};
+use crate::{
+    geometry::{GeometryView, Instances},
+    material::Material,
+    math::{Aabb, BoundingSphere},
+};
+use nalgebra::{Isometry3, Matrix4, Point3, Translation3, UnitQuaternion, UnitVector3, Vector3};
 
 pub struct Mesh {
-    pub children: Vec<Mesh>,
     pub geometry: Box<dyn GeometryView>,
     pub material: Box<dyn Material>,
+    pub depth_test: bool,
+    // Layers the mesh over or under everything with a lower or higher z_index, whatever their
+    // depth. Any non-zero value therefore also turns off depth testing and writing for the mesh,
+    // so its own faces draw in geometry order. Meant for flat overlays and backdrops, solid 3D
+    // meshes should stay at 0 to keep occluding themselves
+    pub z_index: i32,
+    pub instances: Option<Instances>,
 
+    // Only filled before insertion, Scene moves children into slots of their own
+    pub(crate) children: Vec<Mesh>,
     scale: Vector3<f32>,
     isometry: Isometry3<f32>,
     matrix: Matrix4<f32>,
+    world_matrix: Matrix4<f32>,
+    needs_update_world_matrix: bool,
 }
 
 impl Mesh {
     pub fn new<G: GeometryView + 'static, M: Material + 'static>(geometry: G, material: M) -> Self {
         Self {
-            children: Vec::new(),
             geometry: Box::new(geometry),
             material: Box::new(material),
+            depth_test: true,
+            z_index: 0,
+            instances: None,
 
+            children: Vec::new(),
             scale: Vector3::new(1.0, 1.0, 1.0),
             isometry: Isometry3::new(Vector3::zeros(), Vector3::zeros()),
             matrix: Matrix4::identity(),
+            world_matrix: Matrix4::identity(),
+            needs_update_world_matrix: true,
         }
     }
 
-    pub fn add_child(&mut self, child: Mesh) {
+    pub fn new_instanced<G: GeometryView + 'static, M: Material + 'static>(
+        geometry: G,
+        material: M,
+        instances: Instances,
+    ) -> Self {
+        let mut out = Self::new(geometry, material);
+        out.instances = Some(instances);
+        out
+    }
+
+    // Builds a hierarchy before it is added to a scene, use Scene::add_child afterwards
+    pub fn with_child(mut self, mut child: Mesh) -> Self {
+        child.invalidate_world_matrix();
         self.children.push(child);
+        self
     }
 
-    pub fn traverse<F: Fn(&Mesh)>(&self, callback: &F) {
-        callback(self);
+    // Non-empty only for meshes not in a scene, such as ones handed back by Scene::remove_mesh
+    pub fn children(&self) -> &[Mesh] {
+        &self.children
+    }
+
+    pub fn matrix(&self) -> &Matrix4<f32> {
+        &self.matrix
+    }
 
-        for child in &self.children {
-            child.traverse(callback);
+    pub fn world_matrix(&self) -> &Matrix4<f32> {
+        &self.world_matrix
+    }
+
+    // One transform per drawn copy, so instanced meshes yield one per instance
+    pub fn world_transforms(&self) -> Vec<Matrix4<f32>> {
+        match &self.instances {
+            Some(instances) => (0..instances.len())
+                .map(|index| self.world_matrix * instances.transform(index))
+                .collect(),
+            None => vec![self.world_matrix],
         }
     }
 
-    pub fn traverse_mut<F: FnMut(&mut Mesh)>(&mut self, callback: &mut F) {
-        callback(self);
+    // World bounds are only as fresh as the world matrix, see Scene::update_world_matrices
+    pub fn world_bounding_box(&self) -> Option<Aabb> {
+        let local = self.geometry.bounding_box()?;
+
+        self.world_transforms()
+            .iter()
+            .map(|transform| local.transform(transform))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn world_bounding_sphere(&self) -> Option<BoundingSphere> {
+        let local = self.geometry.bounding_sphere()?;
+
+        self.world_transforms()
+            .iter()
+            .map(|transform| local.transform(transform))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn invalidate_buffers(&mut self) {
+        for attr in self.geometry.attributes_mut() {
+            attr.needs_update_buffer = true;
+            attr.needs_update_value = true;
+        }
+
+        if let Some(indices) = self.geometry.indices_mut() {
+            indices.needs_update_buffer = true;
+            indices.needs_update_value = true;
+        }
 
-        for child in &mut self.children {
-            child.traverse_mut(callback);
+        if let Some(instances) = &mut self.instances {
+            instances.needs_update_buffer = true;
+            instances.needs_update_value = true;
         }
     }
 
-    pub fn matrix(&self) -> &Matrix4<f32> {
-        &self.matrix
+    pub fn invalidate_world_matrix(&mut self) {
+        self.needs_update_world_matrix = true;
+    }
+
+    pub fn update_world_matrix(
+        &mut self,
+        parent_world_matrix: Option<&Matrix4<f32>>,
+        force: bool,
+    ) -> bool {
+        // A changed parent invalidates the whole subtree, unchanged subtrees are left as is
+        if !force && !self.needs_update_world_matrix {
+            return false;
+        }
+
+        self.world_matrix = match parent_world_matrix {
+            Some(parent_world_matrix) => parent_world_matrix * self.matrix,
+            None => self.matrix,
+        };
+        self.needs_update_world_matrix = false;
+
+        true
     }
 
     pub fn rotation(&self) -> &UnitQuaternion<f32> {
@@ -78,10 +172,79 @@ impl Mesh {
         self.update_matrix();
     }
 
+    pub fn rotate_axis_angle(&mut self, axis: &UnitVector3<f32>, angle_rad: f32) {
+        self.isometry.rotation =
+            UnitQuaternion::from_axis_angle(axis, angle_rad) * self.isometry.rotation;
+        self.update_matrix();
+    }
+
+    pub fn set_rotation_axis_angle(&mut self, axis: &UnitVector3<f32>, angle_rad: f32) {
+        self.isometry.rotation = UnitQuaternion::from_axis_angle(axis, angle_rad);
+        self.update_matrix();
+    }
+
+    pub fn euler_angles(&self) -> (f32, f32, f32) {
+        self.isometry.rotation.euler_angles()
+    }
+
+    pub fn set_rotation_euler(&mut self, roll: f32, pitch: f32, yaw: f32) {
+        self.isometry.rotation = UnitQuaternion::from_euler_angles(roll, pitch, yaw);
+        self.update_matrix();
+    }
+
+    pub fn rotate_around(&mut self, point: &Point3<f32>, axis: &UnitVector3<f32>, angle_rad: f32) {
+        let rotation = UnitQuaternion::from_axis_angle(axis, angle_rad);
+        let offset = self.isometry.translation.vector - point.coords;
+
+        self.isometry.translation.vector = point.coords + rotation * offset;
+        self.isometry.rotation = rotation * self.isometry.rotation;
+        self.update_matrix();
+    }
+
+    // Turns the local +Z axis towards the target
+    pub fn look_at(&mut self, target: &Point3<f32>, up: &Vector3<f32>) {
+        let direction = target.coords - self.isometry.translation.vector;
+        if direction.norm_squared() == 0.0 {
+            return;
+        }
+
+        // face_towards yields NaN for an up parallel to the direction, the axis furthest from
+        // the direction stands in then
+        let parallel = direction.cross(up).norm_squared()
+            <= f32::EPSILON * direction.norm_squared() * up.norm_squared();
+        let up = if parallel {
+            Vector3::ith(direction.iamin(), 1.0)
+        } else {
+            *up
+        };
+
+        self.isometry.rotation = UnitQuaternion::face_towards(&direction, &up);
+        self.update_matrix();
+    }
+
     pub fn translation(&self) -> &Translation3<f32> {
         &self.isometry.translation
     }
 
+    pub fn set_translation(&mut self, translation: &Translation3<f32>) {
+        self.isometry.translation.clone_from(translation);
+        self.update_matrix();
+    }
+
+    pub fn translate_by(&mut self, offset: &Vector3<f32>) {
+        self.isometry.translation.vector += offset;
+        self.update_matrix();
+    }
+
+    pub fn position(&self) -> Point3<f32> {
+        Point3::from(self.isometry.translation.vector)
+    }
+
+    pub fn set_position(&mut self, position: &Point3<f32>) {
+        self.isometry.translation.vector.copy_from(&position.coords);
+        self.update_matrix();
+    }
+
     pub fn scale(&self) -> &Vector3<f32> {
         &self.scale
     }
@@ -98,5 +261,28 @@ impl Mesh {
 
     fn update_matrix(&mut self) {
         self.matrix = self.isometry.to_homogeneous() * Matrix4::new_nonuniform_scaling(&self.scale);
+        self.needs_update_world_matrix = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{Cube, CubeDescriptor},
+        material::BasicMaterial,
+    };
+
+    #[test]
+    fn look_at_along_up_stays_finite() {
+        let mut mesh = Mesh::new(Cube::new(&CubeDescriptor::default()), BasicMaterial {});
+
+        for up in [Vector3::y(), -Vector3::y(), Vector3::zeros()] {
+            mesh.look_at(&Point3::new(0.0, 5.0, 0.0), &up);
+
+            let forward = mesh.rotation() * Vector3::z();
+            assert!(mesh.matrix().iter().all(|value| value.is_finite()));
+            assert!((forward - Vector3::y()).norm() < 1e-5);
+        }
     }
 }
@@ -6,3 +6,6 @@ pub use cube::*;
 
 mod mesh;
 pub use mesh::Mesh;
+
+mod instances;
+pub use instances::Instances;
@@ -12,3 +12,5 @@ pub mod math;
 pub mod constants;
 
 pub mod animation;
+
+pub mod controls;
@@ -1,4 +1,13 @@
 pub trait Material {
     fn identifier(&self) -> &'static str;
     fn shader_code(&self) -> String;
+
+    // Passing on equal depth lets coplanar meshes drawn later cover the ones drawn before
+    fn depth_compare(&self) -> wgpu::CompareFunction {
+        wgpu::CompareFunction::LessEqual
+    }
+
+    fn depth_write_enabled(&self) -> bool {
+        true
+    }
 }
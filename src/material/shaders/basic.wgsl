@@ -5,13 +5,19 @@
 @group(1) @binding(0) var<storage, read> position: array<f32>;
 @group(1) @binding(1) var<storage, read> color: array<f32>;
 
+@group(2) @binding(0) var<storage, read> instance_transform: array<mat4x4<f32>>;
+@group(2) @binding(1) var<storage, read> instance_color: array<vec4f>;
+
 struct VertexOutput {
     @builtin(position) position: vec4f,
     @location(0) color: vec4f,
 }
  
 @vertex
-fn vs(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+fn vs(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32
+) -> VertexOutput {
     let base_index = vertex_index * 3u;
     let position = vec3f(
         position[base_index],
@@ -26,8 +32,9 @@ fn vs(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     );
 
     var out: VertexOutput;
-    out.position = projection_mat * view_mat * model_mat * vec4f(position, 1.0);
-    out.color = vec4f(color, 1.0);
+    out.position = projection_mat * view_mat * model_mat
+        * instance_transform[instance_index] * vec4f(position, 1.0);
+    out.color = vec4f(color, 1.0) * instance_color[instance_index];
 
     return out;
 }
@@ -0,0 +1,237 @@
+use crate::math::Ray;
+use nalgebra::{Matrix4, Point3, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points(points: &[Point3<f32>]) -> Option<Self> {
+        let first = *points.first()?;
+
+        Some(
+            points
+                .iter()
+                .fold(Self::new(first, first), |aabb, point| Self {
+                    min: aabb.min.inf(point),
+                    max: aabb.max.sup(point),
+                }),
+        )
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    pub fn size(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    pub fn half_extents(&self) -> Vector3<f32> {
+        self.size() / 2.0
+    }
+
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Self {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point3<f32>) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    // Still axis-aligned, so rotations make the box grow
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Aabb {
+        let corners = self.corners().map(|corner| matrix.transform_point(&corner));
+
+        // SAFETY: There are always eight corners
+        Self::from_points(&corners).unwrap()
+    }
+
+    // Distance along the ray to the box, zero if the ray starts inside
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<f32> {
+        let (mut near, mut far) = (0.0_f32, f32::INFINITY);
+
+        for i in 0..3 {
+            let inverse_direction = 1.0 / ray.direction[i];
+            let mut t0 = (self.min[i] - ray.origin[i]) * inverse_direction;
+            let mut t1 = (self.max[i] - ray.origin[i]) * inverse_direction;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            // NaN shows up when the ray lies on a slab plane, max and min skip it
+            near = near.max(t0);
+            far = far.min(t1);
+
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Point3<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    // Centered on the bounding box, which is tight enough and cheap
+    pub fn from_points(points: &[Point3<f32>]) -> Option<Self> {
+        let center = Aabb::from_points(points)?.center();
+        let radius = points
+            .iter()
+            .map(|point| nalgebra::distance(&center, point))
+            .fold(0.0, f32::max);
+
+        Some(Self { center, radius })
+    }
+
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        Self {
+            center: aabb.center(),
+            radius: aabb.half_extents().norm(),
+        }
+    }
+
+    pub fn union(&self, other: &BoundingSphere) -> BoundingSphere {
+        let offset = other.center - self.center;
+        let distance = offset.norm();
+
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+
+        let radius = (distance + self.radius + other.radius) / 2.0;
+        Self {
+            center: self.center + offset * ((radius - self.radius) / distance),
+            radius,
+        }
+    }
+
+    pub fn contains_point(&self, point: &Point3<f32>) -> bool {
+        nalgebra::distance_squared(&self.center, point) <= self.radius * self.radius
+    }
+
+    // Scales the radius by the largest axis scale, so non-uniform scaling stays conservative
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> BoundingSphere {
+        let max_scale = (0..3)
+            .map(|i| matrix.fixed_view::<3, 1>(0, i).norm())
+            .fold(0.0, f32::max);
+
+        Self {
+            center: matrix.transform_point(&self.center),
+            radius: self.radius * max_scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Isometry3, Translation3, UnitQuaternion};
+    use std::f32::consts::FRAC_PI_4;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn merged_boxes_cover_both() {
+        let other = Aabb::new(Point3::new(0.0, 2.0, -3.0), Point3::new(4.0, 3.0, 0.0));
+        let merged = unit_box().union(&other);
+
+        assert_eq!(merged.min, Point3::new(-1.0, -1.0, -3.0));
+        assert_eq!(merged.max, Point3::new(4.0, 3.0, 1.0));
+        assert_eq!(merged, other.union(&unit_box()));
+    }
+
+    #[test]
+    fn transformed_boxes_stay_axis_aligned() {
+        let translated = unit_box().transform(&Translation3::new(1.0, 2.0, 3.0).to_homogeneous());
+        assert_eq!(translated.min, Point3::new(0.0, 1.0, 2.0));
+        assert_eq!(translated.max, Point3::new(2.0, 3.0, 4.0));
+
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_4);
+        let rotated = unit_box().transform(&rotation.to_homogeneous());
+        let half_diagonal = 2.0_f32.sqrt();
+        assert!((rotated.max.x - half_diagonal).abs() < 1e-5);
+        assert!((rotated.max.y - half_diagonal).abs() < 1e-5);
+        assert!((rotated.max.z - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn merged_spheres_cover_both() {
+        let a = BoundingSphere::new(Point3::origin(), 1.0);
+        let b = BoundingSphere::new(Point3::new(4.0, 0.0, 0.0), 1.0);
+        let merged = a.union(&b);
+
+        assert_eq!(merged.center, Point3::new(2.0, 0.0, 0.0));
+        assert_eq!(merged.radius, 3.0);
+        assert!(merged.contains_point(&Point3::new(-1.0, 0.0, 0.0)));
+        assert!(merged.contains_point(&Point3::new(5.0, 0.0, 0.0)));
+
+        // A sphere already inside the other one changes nothing
+        let inner = BoundingSphere::new(Point3::new(0.5, 0.0, 0.0), 0.25);
+        assert_eq!(a.union(&inner), a);
+        assert_eq!(inner.union(&a), a);
+    }
+
+    #[test]
+    fn transformed_spheres_grow_with_the_largest_scale() {
+        let sphere = BoundingSphere::new(Point3::new(1.0, 0.0, 0.0), 1.0);
+        let matrix = Isometry3::translation(0.0, 5.0, 0.0).to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(2.0, 3.0, 1.0));
+        let transformed = sphere.transform(&matrix);
+
+        assert_eq!(transformed.center, Point3::new(2.0, 5.0, 0.0));
+        assert_eq!(transformed.radius, 3.0);
+    }
+
+    #[test]
+    fn spheres_from_points_enclose_them() {
+        let points = unit_box().corners();
+        let sphere = BoundingSphere::from_points(&points).unwrap();
+
+        assert_eq!(sphere.center, Point3::origin());
+        assert!(
+            points
+                .iter()
+                .all(|point| sphere.radius >= point.coords.norm() - 1e-5)
+        );
+        assert!(BoundingSphere::from_points(&[]).is_none());
+    }
+}
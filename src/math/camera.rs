@@ -1,10 +1,108 @@
-use std::f32::consts::PI;
+use std::{any::Any, f32::consts::PI};
 
-use nalgebra::{Isometry3, Matrix4, Perspective3, Point3, Vector3};
+use crate::math::{Frustum, Ray};
+use nalgebra::{
+    Isometry3, Matrix4, Orthographic3, Perspective3, Point2, Point3, Translation3, UnitQuaternion,
+    Vector2, Vector3,
+};
 
 pub trait Camera {
     fn view_mat_data(&self) -> &[u8];
     fn projection_mat_data(&self) -> &[u8];
+    fn view_matrix(&self) -> &Matrix4<f32>;
+    fn projection_matrix(&self) -> &Matrix4<f32>;
+    fn set_aspect(&mut self, aspect: f32);
+
+    fn viewport(&self) -> &Vector2<f32>;
+    fn set_viewport(&mut self, width: f32, height: f32);
+
+    fn view_transform(&self) -> &Isometry3<f32>;
+    fn set_view_transform(&mut self, view_transform: &Isometry3<f32>);
+    fn center(&self) -> &Vector3<f32>;
+    fn set_center(&mut self, center: &Vector3<f32>);
+    fn up(&self) -> &Vector3<f32>;
+    fn look_at(&mut self, target: &Point3<f32>);
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&(self.projection_matrix() * self.view_matrix()))
+    }
+
+    // NDC follows wgpu: x and y in [-1, 1] with y up, depth in [0, 1] from near to far
+    fn world_to_ndc(&self, point: &Point3<f32>) -> Point3<f32> {
+        let clip = self.projection_matrix() * self.view_matrix() * point.to_homogeneous();
+        Point3::from(clip.xyz() / clip.w)
+    }
+
+    fn ndc_to_world(&self, ndc: &Point3<f32>) -> Option<Point3<f32>> {
+        let inverse = (self.projection_matrix() * self.view_matrix()).try_inverse()?;
+        Point3::from_homogeneous(inverse * ndc.to_homogeneous())
+    }
+
+    // Screen space is in pixels, with the origin at the top-left corner of the viewport
+    fn ndc_to_screen(&self, ndc: &Point2<f32>) -> Point2<f32> {
+        let viewport = self.viewport();
+        Point2::new(
+            (ndc.x + 1.0) / 2.0 * viewport.x,
+            (1.0 - ndc.y) / 2.0 * viewport.y,
+        )
+    }
+
+    fn screen_to_ndc(&self, screen: &Point2<f32>) -> Point2<f32> {
+        let viewport = self.viewport();
+        Point2::new(
+            screen.x / viewport.x * 2.0 - 1.0,
+            1.0 - screen.y / viewport.y * 2.0,
+        )
+    }
+
+    // None for points behind the camera
+    fn world_to_screen(&self, point: &Point3<f32>) -> Option<Point2<f32>> {
+        let clip = self.projection_matrix() * self.view_matrix() * point.to_homogeneous();
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        Some(self.ndc_to_screen(&Point2::new(clip.x / clip.w, clip.y / clip.w)))
+    }
+
+    // Starts on the near plane and points into the scene
+    fn screen_to_ray(&self, screen: &Point2<f32>) -> Option<Ray> {
+        let ndc = self.screen_to_ndc(screen);
+        let near = self.ndc_to_world(&Point3::new(ndc.x, ndc.y, 0.0))?;
+        let far = self.ndc_to_world(&Point3::new(ndc.x, ndc.y, 1.0))?;
+
+        Some(Ray::new_normalize(near, far - near))
+    }
+
+    fn screen_to_plane_point(
+        &self,
+        screen: &Point2<f32>,
+        plane_point: &Point3<f32>,
+        plane_normal: &Vector3<f32>,
+    ) -> Option<Point3<f32>> {
+        let ray = self.screen_to_ray(screen)?;
+        let distance = ray.intersect_plane(plane_point, plane_normal)?;
+
+        Some(ray.point_at(distance))
+    }
+}
+
+// Maps the OpenGL clip depth produced by nalgebra, [-1, 1], to the [0, 1] range wgpu expects
+#[rustfmt::skip]
+const OPENGL_TO_WGPU_DEPTH: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.5,
+    0.0, 0.0, 0.0, 1.0,
+);
+
+// View transform of an eye at `center` turned by the scaled axis `rotation`
+fn view_around(center: &Vector3<f32>, rotation: &Vector3<f32>) -> Isometry3<f32> {
+    let rotation = UnitQuaternion::from_scaled_axis(*rotation);
+    Isometry3::from_parts(Translation3::from(-(rotation * center)), rotation)
 }
 
 pub struct PerspectiveCamera {
@@ -16,6 +114,8 @@ pub struct PerspectiveCamera {
 
     pub proj_transform: Perspective3<f32>,
     proj_mat: Matrix4<f32>,
+
+    viewport: Vector2<f32>,
 }
 
 impl PerspectiveCamera {
@@ -24,15 +124,24 @@ impl PerspectiveCamera {
     }
 
     pub fn set_center(&mut self, center: &Vector3<f32>) {
-        self.center.copy_from(&-center);
-        self.view_transform.translation.vector.copy_from(&-center);
+        self.center.copy_from(center);
+        self.view_transform.translation.vector = -(self.view_transform.rotation * center);
         self.view_mat = self.view_transform.to_homogeneous();
     }
 
+    // Turns the camera in place, the eye stays at center
     pub fn set_rotation(&mut self, rotarion: &Vector3<f32>) {
-        self.view_transform =
-            Isometry3::new(self.view_transform.translation.vector, rotarion.clone());
+        self.view_transform = view_around(&self.center, rotarion);
+        self.view_mat = self.view_transform.to_homogeneous();
+    }
+
+    // The center follows the eye of the new transform
+    pub fn set_view_transform(&mut self, view_transform: &Isometry3<f32>) {
+        self.view_transform = *view_transform;
         self.view_mat = self.view_transform.to_homogeneous();
+        self.center = view_transform
+            .inverse_transform_point(&Point3::origin())
+            .coords;
     }
 
     pub fn look_at(&mut self, target: &Point3<f32>) {
@@ -46,22 +155,26 @@ impl PerspectiveCamera {
 
     pub fn set_aspect(&mut self, aspect: f32) {
         self.proj_transform.set_aspect(aspect);
-        self.proj_mat = self.proj_transform.to_homogeneous();
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
     }
 
     pub fn set_far(&mut self, far: f32) {
         self.proj_transform.set_zfar(far);
-        self.proj_mat = self.proj_transform.to_homogeneous();
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
     }
 
     pub fn set_near(&mut self, near: f32) {
         self.proj_transform.set_znear(near);
-        self.proj_mat = self.proj_transform.to_homogeneous();
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
+    }
+
+    pub fn fov_rad(&self) -> f32 {
+        self.proj_transform.fovy()
     }
 
     pub fn set_fov_rad(&mut self, fov_rad: f32) {
-        self.proj_transform.set_zfar(fov_rad);
-        self.proj_mat = self.proj_transform.to_homogeneous();
+        self.proj_transform.set_fovy(fov_rad);
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
     }
 }
 
@@ -77,11 +190,13 @@ impl Default for PerspectiveCamera {
         Self {
             view_mat: view_transform.to_homogeneous(),
             view_transform,
-            proj_mat: proj_transform.to_homogeneous(),
+            proj_mat: OPENGL_TO_WGPU_DEPTH * proj_transform.to_homogeneous(),
             proj_transform,
 
             up: Vector3::y(),
             center,
+
+            viewport: Vector2::new(1.0, 1.0),
         }
     }
 }
@@ -94,4 +209,380 @@ impl Camera for PerspectiveCamera {
     fn projection_mat_data(&self) -> &[u8] {
         bytemuck::cast_slice(self.proj_mat.as_slice())
     }
+
+    fn view_matrix(&self) -> &Matrix4<f32> {
+        &self.view_mat
+    }
+
+    fn projection_matrix(&self) -> &Matrix4<f32> {
+        &self.proj_mat
+    }
+
+    fn set_aspect(&mut self, aspect: f32) {
+        PerspectiveCamera::set_aspect(self, aspect);
+    }
+
+    fn viewport(&self) -> &Vector2<f32> {
+        &self.viewport
+    }
+
+    fn set_viewport(&mut self, width: f32, height: f32) {
+        self.viewport = Vector2::new(width.max(1.0), height.max(1.0));
+        PerspectiveCamera::set_aspect(self, self.viewport.x / self.viewport.y);
+    }
+
+    fn view_transform(&self) -> &Isometry3<f32> {
+        &self.view_transform
+    }
+
+    fn set_view_transform(&mut self, view_transform: &Isometry3<f32>) {
+        PerspectiveCamera::set_view_transform(self, view_transform);
+    }
+
+    fn center(&self) -> &Vector3<f32> {
+        PerspectiveCamera::center(self)
+    }
+
+    fn set_center(&mut self, center: &Vector3<f32>) {
+        PerspectiveCamera::set_center(self, center);
+    }
+
+    fn up(&self) -> &Vector3<f32> {
+        &self.up
+    }
+
+    fn look_at(&mut self, target: &Point3<f32>) {
+        PerspectiveCamera::look_at(self, target);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub struct OrthographicCamera {
+    pub view_transform: Isometry3<f32>,
+    view_mat: Matrix4<f32>,
+
+    pub up: Vector3<f32>,
+    center: Vector3<f32>,
+
+    pub proj_transform: Orthographic3<f32>,
+    proj_mat: Matrix4<f32>,
+
+    frame_height: f32,
+    aspect: f32,
+    zoom: f32,
+
+    viewport: Vector2<f32>,
+}
+
+impl OrthographicCamera {
+    pub fn new(frame_width: f32, frame_height: f32) -> Self {
+        let mut out = Self {
+            aspect: frame_width / frame_height,
+            frame_height,
+            ..Default::default()
+        };
+        out.update_projection();
+        out
+    }
+
+    pub fn center(&self) -> &Vector3<f32> {
+        &self.center
+    }
+
+    pub fn set_center(&mut self, center: &Vector3<f32>) {
+        self.center.copy_from(center);
+        self.view_transform.translation.vector = -(self.view_transform.rotation * center);
+        self.view_mat = self.view_transform.to_homogeneous();
+    }
+
+    // Turns the camera in place, the eye stays at center
+    pub fn set_rotation(&mut self, rotation: &Vector3<f32>) {
+        self.view_transform = view_around(&self.center, rotation);
+        self.view_mat = self.view_transform.to_homogeneous();
+    }
+
+    // The center follows the eye of the new transform
+    pub fn set_view_transform(&mut self, view_transform: &Isometry3<f32>) {
+        self.view_transform = *view_transform;
+        self.view_mat = self.view_transform.to_homogeneous();
+        self.center = view_transform
+            .inverse_transform_point(&Point3::origin())
+            .coords;
+    }
+
+    pub fn look_at(&mut self, target: &Point3<f32>) {
+        self.view_transform = Isometry3::look_at_rh(&Point3::from(self.center), target, &self.up);
+        self.view_mat = self.view_transform.to_homogeneous();
+    }
+
+    pub fn frame_width(&self) -> f32 {
+        self.frame_height * self.aspect
+    }
+
+    pub fn frame_height(&self) -> f32 {
+        self.frame_height
+    }
+
+    pub fn set_frame_width(&mut self, frame_width: f32) {
+        self.frame_height = frame_width / self.aspect;
+        self.update_projection();
+    }
+
+    pub fn set_frame_height(&mut self, frame_height: f32) {
+        self.frame_height = frame_height;
+        self.update_projection();
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    // The frame height is kept on resize, so one world unit keeps its size on screen vertically
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.update_projection();
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    // Zooms at or below zero, which an overshooting rate function may reach, would divide the
+    // frame by zero, so they are clamped to the smallest positive zoom
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(f32::EPSILON);
+        self.update_projection();
+    }
+
+    pub fn set_far(&mut self, far: f32) {
+        self.proj_transform.set_zfar(far);
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
+    }
+
+    pub fn set_near(&mut self, near: f32) {
+        self.proj_transform.set_znear(near);
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
+    }
+
+    fn update_projection(&mut self) {
+        let half_width = self.frame_width() / 2.0 / self.zoom;
+        let half_height = self.frame_height / 2.0 / self.zoom;
+
+        self.proj_transform
+            .set_left_and_right(-half_width, half_width);
+        self.proj_transform
+            .set_bottom_and_top(-half_height, half_height);
+        self.proj_mat = OPENGL_TO_WGPU_DEPTH * self.proj_transform.to_homogeneous();
+    }
+}
+
+impl Default for OrthographicCamera {
+    fn default() -> Self {
+        // Same frame as Manim's default camera
+        let (frame_height, aspect, zoom) = (8.0, 16.0 / 9.0, 1.0);
+        let (far, near) = (1000.0, 0.1);
+        let (center, rotation) = (Vector3::new(0.0, 0.0, 10.0), Vector3::new(0.0, 0.0, 0.0));
+
+        let (half_width, half_height) = (frame_height * aspect / 2.0, frame_height / 2.0);
+        let (view_transform, proj_transform) = (
+            Isometry3::new(-center, rotation),
+            Orthographic3::new(
+                -half_width,
+                half_width,
+                -half_height,
+                half_height,
+                near,
+                far,
+            ),
+        );
+
+        Self {
+            view_mat: view_transform.to_homogeneous(),
+            view_transform,
+            proj_mat: OPENGL_TO_WGPU_DEPTH * proj_transform.to_homogeneous(),
+            proj_transform,
+
+            up: Vector3::y(),
+            center,
+
+            frame_height,
+            aspect,
+            zoom,
+
+            viewport: Vector2::new(1920.0, 1080.0),
+        }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn view_mat_data(&self) -> &[u8] {
+        bytemuck::cast_slice(self.view_mat.as_slice())
+    }
+
+    fn projection_mat_data(&self) -> &[u8] {
+        bytemuck::cast_slice(self.proj_mat.as_slice())
+    }
+
+    fn view_matrix(&self) -> &Matrix4<f32> {
+        &self.view_mat
+    }
+
+    fn projection_matrix(&self) -> &Matrix4<f32> {
+        &self.proj_mat
+    }
+
+    fn set_aspect(&mut self, aspect: f32) {
+        OrthographicCamera::set_aspect(self, aspect);
+    }
+
+    fn viewport(&self) -> &Vector2<f32> {
+        &self.viewport
+    }
+
+    fn set_viewport(&mut self, width: f32, height: f32) {
+        self.viewport = Vector2::new(width.max(1.0), height.max(1.0));
+        OrthographicCamera::set_aspect(self, self.viewport.x / self.viewport.y);
+    }
+
+    fn view_transform(&self) -> &Isometry3<f32> {
+        &self.view_transform
+    }
+
+    fn set_view_transform(&mut self, view_transform: &Isometry3<f32>) {
+        OrthographicCamera::set_view_transform(self, view_transform);
+    }
+
+    fn center(&self) -> &Vector3<f32> {
+        OrthographicCamera::center(self)
+    }
+
+    fn set_center(&mut self, center: &Vector3<f32>) {
+        OrthographicCamera::set_center(self, center);
+    }
+
+    fn up(&self) -> &Vector3<f32> {
+        &self.up
+    }
+
+    fn look_at(&mut self, target: &Point3<f32>) {
+        OrthographicCamera::look_at(self, target);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eye(camera: &dyn Camera) -> Point3<f32> {
+        camera.view_transform().inverse() * Point3::origin()
+    }
+
+    fn assert_near(a: &Point3<f32>, b: &Point3<f32>) {
+        assert!((a - b).norm() < 1e-4, "{a} is not near {b}");
+    }
+
+    #[test]
+    fn rotating_keeps_the_eye_at_the_center() {
+        let center = Vector3::new(1.0, 2.0, 5.0);
+        let rotation = Vector3::new(0.0, 0.5, 0.0);
+
+        let mut perspective = PerspectiveCamera::default();
+        perspective.set_center(&center);
+        perspective.set_rotation(&rotation);
+        assert_near(&eye(&perspective), &center.into());
+
+        let mut orthographic = OrthographicCamera::default();
+        orthographic.set_center(&center);
+        orthographic.set_rotation(&rotation);
+        assert_near(&eye(&orthographic), &center.into());
+    }
+
+    #[test]
+    fn moving_the_center_keeps_the_rotation() {
+        let mut camera = PerspectiveCamera::default();
+        camera.set_rotation(&Vector3::new(0.3, 0.0, 0.0));
+        let rotation = camera.view_transform().rotation;
+
+        camera.set_center(&Vector3::new(0.0, 0.0, 8.0));
+        assert_near(&eye(&camera), &Point3::new(0.0, 0.0, 8.0));
+        assert!(camera.view_transform().rotation.angle_to(&rotation) < 1e-5);
+    }
+
+    fn cameras() -> Vec<Box<dyn Camera>> {
+        let mut perspective = PerspectiveCamera::default();
+        let mut orthographic = OrthographicCamera::default();
+        for camera in [&mut perspective as &mut dyn Camera, &mut orthographic] {
+            camera.set_viewport(800.0, 600.0);
+            camera.set_center(&Vector3::new(3.0, 2.0, 6.0));
+            camera.look_at(&Point3::new(0.5, 0.0, 0.0));
+        }
+
+        vec![Box::new(perspective), Box::new(orthographic)]
+    }
+
+    #[test]
+    fn world_points_survive_a_round_trip_through_ndc() {
+        let point = Point3::new(0.4, -0.3, 0.2);
+
+        for camera in cameras() {
+            let ndc = camera.world_to_ndc(&point);
+            assert!((0.0..=1.0).contains(&ndc.z));
+            assert_near(&camera.ndc_to_world(&ndc).unwrap(), &point);
+        }
+    }
+
+    #[test]
+    fn screen_points_unproject_onto_the_plane_they_came_from() {
+        let point = Point3::new(0.4, -0.3, 0.2);
+        let normal = Vector3::new(0.2, 1.0, 0.5);
+
+        for camera in cameras() {
+            let screen = camera.world_to_screen(&point).unwrap();
+            let unprojected = camera
+                .screen_to_plane_point(&screen, &point, &normal)
+                .unwrap();
+            assert_near(&unprojected, &point);
+        }
+    }
+
+    #[test]
+    fn screen_space_starts_at_the_top_left() {
+        for camera in cameras() {
+            let top_left = camera.ndc_to_screen(&Point2::new(-1.0, 1.0));
+            assert_eq!(top_left, Point2::origin());
+
+            let screen = Point2::new(200.0, 450.0);
+            let ndc = camera.screen_to_ndc(&screen);
+            assert!((camera.ndc_to_screen(&ndc) - screen).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn the_view_target_lands_in_the_middle_of_the_screen() {
+        let camera = &cameras()[0];
+        let screen = camera.world_to_screen(&Point3::new(0.5, 0.0, 0.0)).unwrap();
+        assert!((screen - Point2::new(400.0, 300.0)).norm() < 1e-2);
+
+        // Behind the eye
+        assert!(
+            camera
+                .world_to_screen(&Point3::new(6.0, 4.0, 12.0))
+                .is_none()
+        );
+    }
 }
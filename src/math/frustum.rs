@@ -0,0 +1,110 @@
+use crate::math::{Aabb, BoundingSphere};
+use nalgebra::{Matrix4, Point3, Vector4};
+
+// Planes are stored as (normal, distance) with normals pointing inwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    // Expects wgpu clip space, where depth runs from 0 to w
+    pub fn from_matrix(view_projection: &Matrix4<f32>) -> Self {
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        let planes = [w + x, w - x, w + y, w - y, z, w - z].map(|plane| {
+            let length = plane.xyz().norm();
+            if length > 0.0 { plane / length } else { plane }
+        });
+
+        Self { planes }
+    }
+
+    pub fn contains_point(&self, point: &Point3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&point.coords) + plane.w >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&sphere.center.coords) + plane.w >= -sphere.radius)
+    }
+
+    // Conservative, boxes near the frustum corners may be kept although they are outside
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let farthest_along = |i: usize| {
+                if plane[i] >= 0.0 {
+                    aabb.max[i]
+                } else {
+                    aabb.min[i]
+                }
+            };
+            let farthest = Point3::new(farthest_along(0), farthest_along(1), farthest_along(2));
+
+            plane.xyz().dot(&farthest.coords) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Camera, PerspectiveCamera};
+
+    // Looks down -z from z = 5 with a 45° field of view, near at 0.1 and far at 1000
+    fn camera_frustum() -> Frustum {
+        PerspectiveCamera::default().frustum()
+    }
+
+    #[test]
+    fn points_are_kept_on_the_inner_side_of_every_plane() {
+        let frustum = camera_frustum();
+
+        // One point just inside and one just outside each plane: left, right, bottom, top,
+        // near and far
+        let pairs = [
+            (Point3::new(-1.5, 0.0, 0.0), Point3::new(-3.0, 0.0, 0.0)),
+            (Point3::new(1.5, 0.0, 0.0), Point3::new(3.0, 0.0, 0.0)),
+            (Point3::new(0.0, -1.5, 0.0), Point3::new(0.0, -3.0, 0.0)),
+            (Point3::new(0.0, 1.5, 0.0), Point3::new(0.0, 3.0, 0.0)),
+            (Point3::new(0.0, 0.0, 4.8), Point3::new(0.0, 0.0, 4.95)),
+            (
+                Point3::new(0.0, 0.0, -900.0),
+                Point3::new(0.0, 0.0, -1100.0),
+            ),
+        ];
+
+        for (plane, (inside, outside)) in frustum.planes.iter().zip(pairs) {
+            let distance = |point: &Point3<f32>| plane.xyz().dot(&point.coords) + plane.w;
+            assert!(distance(&inside) > 0.0, "{inside} is outside {plane}");
+            assert!(distance(&outside) < 0.0, "{outside} is inside {plane}");
+
+            assert!(frustum.contains_point(&inside));
+            assert!(!frustum.contains_point(&outside));
+        }
+    }
+
+    #[test]
+    fn points_behind_the_camera_are_outside() {
+        assert!(!camera_frustum().contains_point(&Point3::new(0.0, 0.0, 10.0)));
+    }
+
+    #[test]
+    fn spheres_and_boxes_crossing_a_plane_are_kept() {
+        let frustum = camera_frustum();
+
+        let crossing = BoundingSphere::new(Point3::new(3.0, 0.0, 0.0), 1.5);
+        let outside = BoundingSphere::new(Point3::new(5.0, 0.0, 0.0), 1.5);
+        assert!(frustum.intersects_sphere(&crossing));
+        assert!(!frustum.intersects_sphere(&outside));
+
+        let crossing = Aabb::new(Point3::new(1.5, -0.5, -0.5), Point3::new(3.0, 0.5, 0.5));
+        let outside = Aabb::new(Point3::new(4.0, -0.5, -0.5), Point3::new(5.0, 0.5, 0.5));
+        assert!(frustum.intersects_aabb(&crossing));
+        assert!(!frustum.intersects_aabb(&outside));
+    }
+}
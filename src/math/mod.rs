@@ -1,5 +1,14 @@
 mod camera;
 pub use camera::*;
 
+mod bounds;
+pub use bounds::*;
+
+mod frustum;
+pub use frustum::*;
+
+mod ray;
+pub use ray::*;
+
 mod traits;
 pub use traits::*;
@@ -0,0 +1,72 @@
+use nalgebra::{Point3, UnitVector3, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: UnitVector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: UnitVector3<f32>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn new_normalize(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: UnitVector3::new_normalize(direction),
+        }
+    }
+
+    pub fn point_at(&self, distance: f32) -> Point3<f32> {
+        self.origin + self.direction.into_inner() * distance
+    }
+
+    // Distance along the ray, None if the ray is parallel to the plane or points away from it
+    pub fn intersect_plane(
+        &self,
+        plane_point: &Point3<f32>,
+        plane_normal: &Vector3<f32>,
+    ) -> Option<f32> {
+        let denominator = self.direction.dot(plane_normal);
+        if denominator.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let distance = (plane_point - self.origin).dot(plane_normal) / denominator;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    // Möller–Trumbore, both faces of the triangle are hit
+    pub fn intersect_triangle(
+        &self,
+        a: &Point3<f32>,
+        b: &Point3<f32>,
+        c: &Point3<f32>,
+    ) -> Option<f32> {
+        let (edge_ab, edge_ac) = (b - a, c - a);
+
+        let p = self.direction.cross(&edge_ac);
+        let determinant = edge_ab.dot(&p);
+        if determinant.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let to_origin = self.origin - a;
+
+        let u = to_origin.dot(&p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(&edge_ab);
+        let v = self.direction.dot(&q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_ac.dot(&q) * inverse_determinant;
+        (distance >= 0.0).then_some(distance)
+    }
+}
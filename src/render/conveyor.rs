@@ -4,6 +4,15 @@ use std::collections::HashMap;
 struct Gadget {
     buffer: wgpu::Buffer,
     ty: wgpu::BufferBindingType,
+    binding_size: Option<wgpu::BufferSize>,
+    has_dynamic_offset: bool,
+    dynamic_offset: u32,
+}
+
+#[derive(Debug)]
+struct IndexGadget {
+    buffer: wgpu::Buffer,
+    format: wgpu::IndexFormat,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -18,17 +27,22 @@ pub struct GadgetDescriptor<'a> {
     pub size: u64,
     pub usage: wgpu::BufferUsages,
     pub ty: wgpu::BufferBindingType,
+    pub binding_size: Option<wgpu::BufferSize>,
+    pub has_dynamic_offset: bool,
 }
 
 #[derive(Debug)]
 pub struct Bundle {
     bind_group: wgpu::BindGroup,
     bind_group_layout: wgpu::BindGroupLayout,
+    dynamic_gadget_labels: Vec<String>,
 }
 
 #[derive(Debug)]
 pub enum ConveyorError {
     UnknownGadgetLabel,
+    StaticGadget,
+    MissingIndexGadget,
 }
 
 pub struct Conveyor {
@@ -37,6 +51,7 @@ pub struct Conveyor {
 
     gadgets: HashMap<String, Gadget>,
     indices: Vec<Option<HashMap<u32, String>>>,
+    index_gadget: Option<IndexGadget>,
 }
 
 impl Conveyor {
@@ -45,6 +60,7 @@ impl Conveyor {
             gadgets: HashMap::new(),
             bundles: Vec::new(),
             indices: Vec::new(),
+            index_gadget: None,
             needs_update: false,
         }
     }
@@ -60,6 +76,9 @@ impl Conveyor {
         let gadget = Gadget {
             buffer,
             ty: desc.ty,
+            binding_size: desc.binding_size,
+            has_dynamic_offset: desc.has_dynamic_offset,
+            dynamic_offset: 0,
         };
 
         self.gadgets.insert(String::from(desc.label), gadget);
@@ -97,6 +116,69 @@ impl Conveyor {
         Ok(())
     }
 
+    pub fn upsert_index_gadget(
+        &mut self,
+        device: &wgpu::Device,
+        label: &str,
+        size: u64,
+        format: wgpu::IndexFormat,
+    ) {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.index_gadget = Some(IndexGadget { buffer, format });
+    }
+
+    pub fn update_index_gadget(
+        &mut self,
+        queue: &wgpu::Queue,
+        data: &[u8],
+    ) -> Result<(), ConveyorError> {
+        let index_gadget = self
+            .index_gadget
+            .as_ref()
+            .ok_or(ConveyorError::MissingIndexGadget)?;
+
+        queue.write_buffer(&index_gadget.buffer, 0, data);
+
+        Ok(())
+    }
+
+    pub fn attach_index_gadget(&self, render_pass: &mut wgpu::RenderPass) {
+        if let Some(index_gadget) = &self.index_gadget {
+            render_pass.set_index_buffer(index_gadget.buffer.slice(..), index_gadget.format);
+        }
+    }
+
+    pub fn gadget_size(&self, gadget_label: &str) -> Option<u64> {
+        self.gadgets
+            .get(gadget_label)
+            .map(|gadget| gadget.buffer.size())
+    }
+
+    pub fn set_dynamic_offset(
+        &mut self,
+        gadget_label: &str,
+        offset: u32,
+    ) -> Result<(), ConveyorError> {
+        let gadget = self
+            .gadgets
+            .get_mut(gadget_label)
+            .ok_or(ConveyorError::UnknownGadgetLabel)?;
+
+        if !gadget.has_dynamic_offset {
+            return Err(ConveyorError::StaticGadget);
+        }
+
+        gadget.dynamic_offset = offset;
+
+        Ok(())
+    }
+
     pub fn update_bundles(&mut self, device: &wgpu::Device) {
         self.bundles = Vec::new();
 
@@ -111,26 +193,39 @@ impl Conveyor {
 
             let group_desc = group_desc.as_ref().unwrap();
 
+            // Dynamic offsets must be passed in binding order
+            let mut dynamic_bindings: Vec<(u32, String)> = Vec::new();
+
             for (binding_index, gadget_label) in group_desc {
                 let gadget = self.gadgets.get(gadget_label).unwrap();
 
+                if gadget.has_dynamic_offset {
+                    dynamic_bindings.push((*binding_index, gadget_label.clone()));
+                }
+
                 bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
                     binding: *binding_index,
                     visibility: wgpu::ShaderStages::VERTEX_FRAGMENT, // Hard coded currently
                     ty: wgpu::BindingType::Buffer {
                         ty: gadget.ty,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                        has_dynamic_offset: gadget.has_dynamic_offset,
+                        min_binding_size: gadget.binding_size,
                     },
                     count: None,
                 });
 
                 bind_group_entries.push(wgpu::BindGroupEntry {
                     binding: *binding_index,
-                    resource: gadget.buffer.as_entire_binding(),
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &gadget.buffer,
+                        offset: 0,
+                        size: gadget.binding_size,
+                    }),
                 })
             }
 
+            dynamic_bindings.sort_by_key(|(binding_index, _)| *binding_index);
+
             let bind_group_layout =
                 device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                     label: Some(&format!(
@@ -149,6 +244,10 @@ impl Conveyor {
             let bundle = Bundle {
                 bind_group: bind_group,
                 bind_group_layout: bind_group_layout,
+                dynamic_gadget_labels: dynamic_bindings
+                    .into_iter()
+                    .map(|(_, gadget_label)| gadget_label)
+                    .collect(),
             };
 
             self.bundles.push(Some(bundle));
@@ -160,7 +259,13 @@ impl Conveyor {
     pub fn attach_bundles(&self, render_pass: &mut wgpu::RenderPass) {
         for (index, maybe_bundle) in self.bundles.iter().enumerate() {
             if let Some(bundle) = maybe_bundle {
-                render_pass.set_bind_group(index as u32, &bundle.bind_group, &[]);
+                let dynamic_offsets: Vec<u32> = bundle
+                    .dynamic_gadget_labels
+                    .iter()
+                    .map(|gadget_label| self.gadgets.get(gadget_label).unwrap().dynamic_offset)
+                    .collect();
+
+                render_pass.set_bind_group(index as u32, &bundle.bind_group, &dynamic_offsets);
             }
         }
     }
@@ -22,4 +22,12 @@ impl ConveyorManager {
         // SAFETY: Checked upon
         self.conveyor_pool.get_mut(identifier).unwrap()
     }
+
+    pub fn release_conveyor(&mut self, identifier: &str) -> Option<Conveyor> {
+        self.conveyor_pool.remove(identifier)
+    }
+
+    pub fn get_conveyor(&self, identifier: &str) -> Option<&Conveyor> {
+        self.conveyor_pool.get(identifier)
+    }
 }
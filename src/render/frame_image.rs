@@ -0,0 +1,32 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+pub struct FrameImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl FrameImage {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * self.width + x) * 4) as usize;
+        [
+            self.data[offset],
+            self.data[offset + 1],
+            self.data[offset + 2],
+            self.data[offset + 3],
+        ]
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), png::EncodingError> {
+        let file = File::create(path)?;
+
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.data)?;
+
+        Ok(())
+    }
+}
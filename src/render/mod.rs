@@ -1,11 +1,17 @@
 mod renderer;
-pub use renderer::Renderer;
+pub use renderer::{DEPTH_FORMAT, RenderStats, Renderer, RendererError};
 
 mod conveyor;
 pub use conveyor::*;
 
 mod pipeline_manager;
-pub use pipeline_manager::PipelineManager;
+pub use pipeline_manager::{PipelineKey, PipelineManager};
 
 mod conveyor_manager;
 pub use conveyor_manager::ConveyorManager;
+
+mod render_target;
+pub use render_target::{RenderTarget, TargetFrame};
+
+mod frame_image;
+pub use frame_image::FrameImage;
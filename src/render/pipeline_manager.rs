@@ -1,8 +1,49 @@
-use crate::material::Material;
+use crate::render::DEPTH_FORMAT;
+use crate::{geometry::Mesh, material::Material};
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub identifier: &'static str,
+    pub topology: wgpu::PrimitiveTopology,
+    pub strip_index_format: Option<wgpu::IndexFormat>,
+    pub depth_compare: wgpu::CompareFunction,
+    pub depth_write_enabled: bool,
+    pub sample_count: u32,
+}
+
+impl PipelineKey {
+    pub fn from_mesh(mesh: &Mesh, sample_count: u32) -> Self {
+        // Meshes with a z_index are layered by it alone, see Mesh::z_index
+        let (depth_compare, depth_write_enabled) = if mesh.depth_test && mesh.z_index == 0 {
+            (
+                mesh.material.depth_compare(),
+                mesh.material.depth_write_enabled(),
+            )
+        } else {
+            (wgpu::CompareFunction::Always, false)
+        };
+
+        let topology = mesh.geometry.topology();
+        let strip_index_format = if topology.is_strip() {
+            mesh.geometry.indices().map(|indices| indices.data.format())
+        } else {
+            None
+        };
+
+        Self {
+            identifier: mesh.material.identifier(),
+            topology,
+            strip_index_format,
+            depth_compare,
+            depth_write_enabled,
+            sample_count,
+        }
+    }
+}
+
 pub struct PipelineManager {
-    pub pipeline_pool: HashMap<String, wgpu::RenderPipeline>,
+    pub pipeline_pool: HashMap<PipelineKey, wgpu::RenderPipeline>,
 }
 
 impl PipelineManager {
@@ -17,12 +58,11 @@ impl PipelineManager {
         device: &wgpu::Device,
         texture_format: wgpu::TextureFormat,
         material: &dyn Material,
+        key: &PipelineKey,
         bind_groups: &[&wgpu::BindGroupLayout],
         force_update: bool,
     ) -> &wgpu::RenderPipeline {
-        let pipeline_identifier = material.identifier();
-
-        if !self.pipeline_pool.contains_key(pipeline_identifier) || force_update {
+        if !self.pipeline_pool.contains_key(key) || force_update {
             let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
                 label: Some("Mraphics Shader"),
                 source: wgpu::ShaderSource::Wgsl(material.shader_code().into()),
@@ -55,25 +95,37 @@ impl PipelineManager {
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                 }),
                 primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::LineList,
-                    strip_index_format: None,
+                    topology: key.topology,
+                    strip_index_format: key.strip_index_format,
                     front_face: wgpu::FrontFace::Ccw,
                     cull_mode: None,
                     polygon_mode: wgpu::PolygonMode::Fill,
                     unclipped_depth: false,
                     conservative: false,
                 },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: key.depth_write_enabled,
+                    depth_compare: key.depth_compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: key.sample_count,
+                    ..Default::default()
+                },
                 multiview: None,
                 cache: None,
             });
 
-            self.pipeline_pool
-                .insert(String::from(material.identifier()), render_pipeline);
+            self.pipeline_pool.insert(*key, render_pipeline);
         }
 
         // SAFETY: Checked upon
-        self.pipeline_pool.get(pipeline_identifier).unwrap()
+        self.pipeline_pool.get(key).unwrap()
+    }
+
+    pub fn get_pipeline(&self, key: &PipelineKey) -> Option<&wgpu::RenderPipeline> {
+        self.pipeline_pool.get(key)
     }
 }
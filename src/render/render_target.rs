@@ -0,0 +1,130 @@
+pub enum RenderTarget<'window> {
+    Surface {
+        surface: wgpu::Surface<'window>,
+        config: wgpu::SurfaceConfiguration,
+    },
+    Texture {
+        texture: wgpu::Texture,
+    },
+}
+
+pub enum TargetFrame {
+    Surface(wgpu::SurfaceTexture),
+    Texture(wgpu::Texture),
+}
+
+impl TargetFrame {
+    pub fn texture(&self) -> &wgpu::Texture {
+        match self {
+            TargetFrame::Surface(surface_texture) => &surface_texture.texture,
+            TargetFrame::Texture(texture) => texture,
+        }
+    }
+
+    pub fn present(self) {
+        if let TargetFrame::Surface(surface_texture) = self {
+            surface_texture.present();
+        }
+    }
+}
+
+impl<'window> RenderTarget<'window> {
+    pub fn from_surface(
+        surface: wgpu::Surface<'window>,
+        device: &wgpu::Device,
+        adapter: &wgpu::Adapter,
+    ) -> Self {
+        let surface_caps = surface.get_capabilities(adapter);
+        let config = wgpu::SurfaceConfiguration {
+            width: 100,
+            height: 100,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_caps.formats[0],
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        surface.configure(device, &config);
+
+        Self::Surface { surface, config }
+    }
+
+    pub fn from_texture_size(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::Texture {
+            texture: Self::create_texture(device, width, height, format),
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            RenderTarget::Surface { config, .. } => config.format,
+            RenderTarget::Texture { texture } => texture.format(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            RenderTarget::Surface { config, .. } => config.width,
+            RenderTarget::Texture { texture } => texture.width(),
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            RenderTarget::Surface { config, .. } => config.height,
+            RenderTarget::Texture { texture } => texture.height(),
+        }
+    }
+
+    pub fn acquire_frame(&self) -> Result<TargetFrame, wgpu::SurfaceError> {
+        match self {
+            RenderTarget::Surface { surface, .. } => {
+                Ok(TargetFrame::Surface(surface.get_current_texture()?))
+            }
+            RenderTarget::Texture { texture } => Ok(TargetFrame::Texture(texture.clone())),
+        }
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match self {
+            RenderTarget::Surface { surface, config } => {
+                config.width = width;
+                config.height = height;
+
+                surface.configure(device, config);
+            }
+            RenderTarget::Texture { texture } => {
+                *texture = Self::create_texture(device, width, height, texture.format());
+            }
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Mraphics Offscreen Target"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+}
@@ -1,26 +1,71 @@
 use crate::{
     Scene,
-    geometry::Mesh,
-    math::Camera,
-    render::{Conveyor, ConveyorManager, PipelineManager, conveyor::GadgetDescriptor},
+    geometry::{Instances, Mesh},
+    render::{
+        Conveyor, ConveyorManager, FrameImage, PipelineKey, PipelineManager, RenderTarget,
+        conveyor::GadgetDescriptor,
+    },
 };
 
 use crate::constants::{
-    MODEL_MAT_INDEX, MODEL_MAT_LABEL, PROJECTION_MAT_INDEX, PROJECTION_MAT_LABEL, VIEW_MAT_INDEX,
+    INDEX_LABEL, INSTANCE_COLOR_ATTR_INDEX, INSTANCE_COLOR_ATTR_LABEL,
+    INSTANCE_TRANSFORM_ATTR_INDEX, INSTANCE_TRANSFORM_ATTR_LABEL, MODEL_MAT_INDEX, MODEL_MAT_LABEL,
+    POSITION_ATTR_LABEL, PROJECTION_MAT_INDEX, PROJECTION_MAT_LABEL, VIEW_MAT_INDEX,
     VIEW_MAT_LABEL,
 };
+use nalgebra::{Matrix4, Vector4};
+
+const MODEL_MAT_SIZE: u64 = 4 * 4 * 4;
+
+pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+#[derive(Debug)]
+pub enum RendererError {
+    AdapterUnavailable(wgpu::RequestAdapterError),
+    DeviceUnavailable(wgpu::RequestDeviceError),
+    UnreadableTarget,
+    UnsupportedFormat(wgpu::TextureFormat),
+    PollFailed(wgpu::PollError),
+    MapFailed(wgpu::BufferAsyncError),
+    UnsupportedSampleCount(u32),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub drawn_meshes: u32,
+    pub culled_meshes: u32,
+}
 
 pub struct Renderer<'window> {
-    pub surface: wgpu::Surface<'window>,
-    pub surface_config: wgpu::SurfaceConfiguration,
+    pub target: RenderTarget<'window>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
 
     pub clear_color: [f64; 4],
+    pub frustum_culling: bool,
 
     pipeline_manager: PipelineManager,
     conveyor_manager: ConveyorManager,
     shared_conveyor: Conveyor,
+    default_instance_conveyor: Conveyor,
+    model_mat_stride: u64,
+    sample_count: u32,
+    multisample_flags: wgpu::TextureFormatFeatureFlags,
+    depth_texture: wgpu::Texture,
+    msaa_texture: Option<wgpu::Texture>,
+    stats: RenderStats,
+}
+
+struct DrawCall {
+    conveyor_identifier: String,
+    instance_conveyor_identifier: Option<String>,
+    pipeline_key: PipelineKey,
+    depth_test: bool,
+    z_index: i32,
+    vertex_count: u32,
+    index_count: Option<u32>,
+    instance_count: u32,
+    model_mat_offset: u32,
 }
 
 impl<'window> Renderer<'window> {
@@ -30,20 +75,16 @@ impl<'window> Renderer<'window> {
         queue: wgpu::Queue,
         adapter: &wgpu::Adapter,
     ) -> Self {
-        let surface_caps = surface.get_capabilities(adapter);
-        let surface_config = wgpu::SurfaceConfiguration {
-            width: 100,
-            height: 100,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_caps.formats[0],
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        surface.configure(&device, &surface_config);
+        let target = RenderTarget::from_surface(surface, &device, adapter);
+        Self::with_target(target, device, queue, adapter)
+    }
 
+    pub fn with_target(
+        target: RenderTarget<'window>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        adapter: &wgpu::Adapter,
+    ) -> Self {
         let mut shared_conveyor = Conveyor::new();
         shared_conveyor.upsert_gadget(
             &device,
@@ -53,6 +94,8 @@ impl<'window> Renderer<'window> {
                 size: 4 * 4 * 4,
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
                 ty: wgpu::BufferBindingType::Uniform,
+                binding_size: None,
+                has_dynamic_offset: false,
             },
         );
         shared_conveyor.upsert_gadget(
@@ -63,40 +106,77 @@ impl<'window> Renderer<'window> {
                 size: 4 * 4 * 4,
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
                 ty: wgpu::BufferBindingType::Uniform,
+                binding_size: None,
+                has_dynamic_offset: false,
             },
         );
 
+        // Every mesh owns a slot of the model matrix gadget, addressed by a dynamic offset
+        let offset_alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let model_mat_stride = MODEL_MAT_SIZE.div_ceil(offset_alignment) * offset_alignment;
+
         shared_conveyor.upsert_gadget(
             &device,
             &GadgetDescriptor {
                 label: MODEL_MAT_LABEL,
                 index: MODEL_MAT_INDEX,
-                size: 4 * 4 * 4,
+                size: model_mat_stride,
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
                 ty: wgpu::BufferBindingType::Uniform,
+                binding_size: wgpu::BufferSize::new(MODEL_MAT_SIZE),
+                has_dynamic_offset: true,
             },
         );
 
+        // Meshes without instances are drawn as a single untransformed white instance
+        let mut default_instances = Instances::new();
+        default_instances.push(Matrix4::identity(), Vector4::new(1.0, 1.0, 1.0, 1.0));
+
+        let mut default_instance_conveyor = Conveyor::new();
+        Self::upload_instances(
+            &device,
+            &queue,
+            &mut default_instance_conveyor,
+            &mut default_instances,
+        );
+
+        // Multisampled draws need both the color and the depth attachment to support the count
+        let color_features = Self::format_features(adapter, &device, target.format());
+        let depth_features = Self::format_features(adapter, &device, DEPTH_FORMAT);
+        let multisample_flags = if color_features
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE)
+        {
+            color_features.flags & depth_features.flags
+        } else {
+            wgpu::TextureFormatFeatureFlags::empty()
+        };
+
+        let depth_texture = Self::create_depth_texture(&device, target.width(), target.height(), 1);
+
         Self {
-            surface,
-            surface_config,
+            target,
             device,
             queue,
             clear_color: [0., 0., 0., 1.],
+            frustum_culling: true,
             pipeline_manager: PipelineManager::new(),
             conveyor_manager: ConveyorManager::new(),
             shared_conveyor,
+            default_instance_conveyor,
+            model_mat_stride,
+            sample_count: 1,
+            multisample_flags,
+            depth_texture,
+            msaa_texture: None,
+            stats: RenderStats::default(),
         }
     }
 
-    pub fn render<C: Camera>(
-        &mut self,
-        scene: &mut Scene,
-        camera: &C,
-    ) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
+    pub fn render(&mut self, scene: &mut Scene) -> Result<(), wgpu::SurfaceError> {
+        let output = self.target.acquire_frame()?;
         let view = output
-            .texture
+            .texture()
             .create_view(&wgpu::TextureViewDescriptor::default());
 
         let mut encoder = self
@@ -105,11 +185,85 @@ impl<'window> Renderer<'window> {
                 label: Some("Mraphics Command Encoder"),
             });
 
+        let camera = scene.camera();
+        let frustum = camera.frustum();
+
+        // SAFETY: initialized these gadgets in Renderer::new()
+        self.shared_conveyor
+            .update_gadget(&self.queue, VIEW_MAT_LABEL, camera.view_mat_data())
+            .unwrap();
+        self.shared_conveyor
+            .update_gadget(
+                &self.queue,
+                PROJECTION_MAT_LABEL,
+                camera.projection_mat_data(),
+            )
+            .unwrap();
+
+        for identifier in scene.take_released_identifiers() {
+            self.conveyor_manager.release_conveyor(&identifier);
+        }
+
+        scene.update_world_matrices();
+
+        let mut draw_calls: Vec<DrawCall> = Vec::new();
+        let mut model_mat_data: Vec<u8> = Vec::new();
+        let mut stats = RenderStats::default();
+
+        scene.traverse_mut(&mut |mesh: &mut Mesh| {
+            // An instanced mesh with no instances left has nothing to draw, so it is not counted
+            if mesh
+                .instances
+                .as_ref()
+                .is_some_and(|instances| instances.is_empty())
+            {
+                return;
+            }
+
+            // Meshes without bounds have no positions to cull by and are always drawn
+            if self.frustum_culling
+                && mesh
+                    .world_bounding_box()
+                    .is_some_and(|bounds| !frustum.intersects_aabb(&bounds))
+            {
+                stats.culled_meshes += 1;
+                return;
+            }
+            stats.drawn_meshes += 1;
+
+            let model_mat_offset = model_mat_data.len() as u32;
+            model_mat_data.extend_from_slice(bytemuck::cast_slice(mesh.world_matrix().as_slice()));
+            model_mat_data.resize(
+                model_mat_offset as usize + self.model_mat_stride as usize,
+                0,
+            );
+
+            draw_calls.push(self.prepare_mesh(mesh, model_mat_offset));
+        });
+
+        self.upload_model_mats(&model_mat_data);
+        self.stats = stats;
+
+        // Lower z-indices are drawn first, and within the same z-index meshes opted out of
+        // depth testing are drawn last so they stay on top. The sort is stable, so scene order
+        // breaks the remaining ties, and coplanar meshes drawn later win the depth test.
+        draw_calls.sort_by_key(|draw_call| (draw_call.z_index, !draw_call.depth_test));
+
+        let depth_view = self
+            .depth_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // When multisampling, draw into the MSAA texture and resolve into the target
+        let msaa_view = self
+            .msaa_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Mraphics Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
+                view: msaa_view.as_ref().unwrap_or(&view),
+                resolve_target: msaa_view.as_ref().map(|_| &view),
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color {
                         r: self.clear_color[0],
@@ -120,24 +274,20 @@ impl<'window> Renderer<'window> {
                     store: wgpu::StoreOp::Store,
                 },
             })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
             ..Default::default()
         });
 
-        // SAFETY: initialized these gadgets in Renderer::new()
-        self.shared_conveyor
-            .update_gadget(&self.queue, VIEW_MAT_LABEL, camera.view_mat_data())
-            .unwrap();
-        self.shared_conveyor
-            .update_gadget(
-                &self.queue,
-                PROJECTION_MAT_LABEL,
-                camera.projection_mat_data(),
-            )
-            .unwrap();
-
-        scene.traverse_mut(&mut |mesh: &mut Mesh| {
-            self.render_mesh(&mut render_pass, mesh);
-        });
+        for draw_call in draw_calls.iter() {
+            self.draw(&mut render_pass, draw_call);
+        }
 
         drop(render_pass);
 
@@ -148,20 +298,12 @@ impl<'window> Renderer<'window> {
         Ok(())
     }
 
-    pub fn render_mesh(&mut self, render_pass: &mut wgpu::RenderPass, mesh: &mut Mesh) {
-        // SAFETY: initialized this gadget in Renderer::new()
-        self.shared_conveyor
-            .update_gadget(
-                &self.queue,
-                MODEL_MAT_LABEL,
-                bytemuck::cast_slice(mesh.matrix().as_slice()),
-            )
-            .unwrap();
-
+    fn prepare_mesh(&mut self, mesh: &mut Mesh, model_mat_offset: u32) -> DrawCall {
         let attr_conveyor = self
             .conveyor_manager
             .acquire_attr_conveyor(mesh.geometry.identifier());
 
+        let mut positions_uploaded = false;
         for attr in mesh.geometry.attributes_mut() {
             if attr.needs_update_buffer {
                 attr_conveyor.upsert_gadget(
@@ -172,6 +314,8 @@ impl<'window> Renderer<'window> {
                         size: attr.data.len() as u64,
                         usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
                         ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        binding_size: None,
+                        has_dynamic_offset: false,
                     },
                 );
 
@@ -188,36 +332,558 @@ impl<'window> Renderer<'window> {
                 .unwrap();
 
             attr.needs_update_value = false;
+            positions_uploaded |= attr.label == POSITION_ATTR_LABEL;
+        }
+
+        // The flag that marked the positions as edited is gone, so cached bounds go with it
+        if positions_uploaded {
+            mesh.geometry.invalidate_bounds();
+        }
+
+        if let Some(indices) = mesh.geometry.indices_mut() {
+            // Buffer writes must be a multiple of COPY_BUFFER_ALIGNMENT
+            let index_size = (indices.data.as_bytes().len() as u64)
+                .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+
+            if indices.needs_update_buffer {
+                attr_conveyor.upsert_index_gadget(
+                    &self.device,
+                    INDEX_LABEL,
+                    index_size,
+                    indices.data.format(),
+                );
+
+                indices.needs_update_buffer = false;
+            }
+
+            if indices.needs_update_value {
+                let mut index_data = indices.data.as_bytes().to_vec();
+                index_data.resize(index_size as usize, 0);
+
+                // SAFETY: The index gadget is created upon
+                attr_conveyor
+                    .update_index_gadget(&self.queue, &index_data)
+                    .unwrap();
+
+                indices.needs_update_value = false;
+            }
+        }
+
+        let attr_needs_update = attr_conveyor.needs_update;
+
+        let instance_identifier = mesh
+            .instances
+            .as_ref()
+            .map(|instances| instances.identifier().to_string());
+
+        if let Some(instances) = mesh.instances.as_mut() {
+            let instance_conveyor = self
+                .conveyor_manager
+                .acquire_attr_conveyor(instances.identifier());
+
+            Self::upload_instances(&self.device, &self.queue, instance_conveyor, instances);
+        }
+
+        let instance_needs_update = match &instance_identifier {
+            Some(identifier) => self.conveyor_manager.acquire_attr_conveyor(identifier),
+            None => &mut self.default_instance_conveyor,
         }
+        .needs_update;
 
-        let needs_update = self.shared_conveyor.needs_update || attr_conveyor.needs_update;
+        let needs_update =
+            self.shared_conveyor.needs_update || attr_needs_update || instance_needs_update;
         if needs_update {
             self.shared_conveyor.update_bundles(&self.device);
-            attr_conveyor.update_bundles(&self.device);
+            self.conveyor_manager
+                .acquire_attr_conveyor(mesh.geometry.identifier())
+                .update_bundles(&self.device);
+
+            match &instance_identifier {
+                Some(identifier) => self.conveyor_manager.acquire_attr_conveyor(identifier),
+                None => &mut self.default_instance_conveyor,
+            }
+            .update_bundles(&self.device);
         }
 
-        let pipeline = self.pipeline_manager.acquire_pipeline(
+        // SAFETY: Both conveyors are acquired upon
+        let attr_conveyor = self
+            .conveyor_manager
+            .get_conveyor(mesh.geometry.identifier())
+            .unwrap();
+        let instance_conveyor = match &instance_identifier {
+            Some(identifier) => self.conveyor_manager.get_conveyor(identifier).unwrap(),
+            None => &self.default_instance_conveyor,
+        };
+
+        let pipeline_key = PipelineKey::from_mesh(mesh, self.sample_count);
+
+        self.pipeline_manager.acquire_pipeline(
             &self.device,
-            self.surface_config.format,
+            self.target.format(),
             mesh.material.as_ref(),
+            &pipeline_key,
             &Conveyor::collect_bind_group_layouts(vec![
                 &self.shared_conveyor.bundles,
                 &attr_conveyor.bundles,
+                &instance_conveyor.bundles,
             ]),
             needs_update,
         );
 
+        DrawCall {
+            conveyor_identifier: mesh.geometry.identifier().to_string(),
+            instance_conveyor_identifier: instance_identifier,
+            pipeline_key,
+            depth_test: mesh.depth_test,
+            z_index: mesh.z_index,
+            vertex_count: mesh.geometry.vertex_count(),
+            index_count: mesh.geometry.indices().map(|indices| indices.data.len()),
+            instance_count: mesh
+                .instances
+                .as_ref()
+                .map_or(1, |instances| instances.len() as u32),
+            model_mat_offset,
+        }
+    }
+
+    fn upload_instances(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        conveyor: &mut Conveyor,
+        instances: &mut Instances,
+    ) {
+        if instances.needs_update_buffer {
+            // Storage bindings must not be empty, so keep room for at least one instance
+            let capacity = instances.len().max(1) as u64;
+
+            conveyor.upsert_gadget(
+                device,
+                &GadgetDescriptor {
+                    label: INSTANCE_TRANSFORM_ATTR_LABEL,
+                    index: INSTANCE_TRANSFORM_ATTR_INDEX,
+                    size: capacity * 4 * 4 * 4,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    binding_size: None,
+                    has_dynamic_offset: false,
+                },
+            );
+            conveyor.upsert_gadget(
+                device,
+                &GadgetDescriptor {
+                    label: INSTANCE_COLOR_ATTR_LABEL,
+                    index: INSTANCE_COLOR_ATTR_INDEX,
+                    size: capacity * 4 * 4,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    binding_size: None,
+                    has_dynamic_offset: false,
+                },
+            );
+
+            instances.needs_update_buffer = false;
+        }
+
+        if instances.needs_update_value {
+            // SAFETY: initialized these gadgets upon
+            conveyor
+                .update_gadget(
+                    queue,
+                    INSTANCE_TRANSFORM_ATTR_LABEL,
+                    bytemuck::cast_slice(&instances.transform_data()),
+                )
+                .unwrap();
+            conveyor
+                .update_gadget(
+                    queue,
+                    INSTANCE_COLOR_ATTR_LABEL,
+                    bytemuck::cast_slice(&instances.color_data()),
+                )
+                .unwrap();
+
+            instances.needs_update_value = false;
+        }
+    }
+
+    fn draw(&mut self, render_pass: &mut wgpu::RenderPass, draw_call: &DrawCall) {
+        // SAFETY: All of these are prepared in Renderer::prepare_mesh()
+        let attr_conveyor = self
+            .conveyor_manager
+            .get_conveyor(&draw_call.conveyor_identifier)
+            .unwrap();
+        let instance_conveyor = match &draw_call.instance_conveyor_identifier {
+            Some(identifier) => self.conveyor_manager.get_conveyor(identifier).unwrap(),
+            None => &self.default_instance_conveyor,
+        };
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline(&draw_call.pipeline_key)
+            .unwrap();
+
+        // SAFETY: initialized this gadget in Renderer::new()
+        self.shared_conveyor
+            .set_dynamic_offset(MODEL_MAT_LABEL, draw_call.model_mat_offset)
+            .unwrap();
+
         self.shared_conveyor.attach_bundles(render_pass);
         attr_conveyor.attach_bundles(render_pass);
+        instance_conveyor.attach_bundles(render_pass);
 
         render_pass.set_pipeline(pipeline);
-        render_pass.draw(0..mesh.geometry.indices(), 0..1);
+
+        let instance_range = 0..draw_call.instance_count;
+        match draw_call.index_count {
+            Some(index_count) => {
+                attr_conveyor.attach_index_gadget(render_pass);
+                render_pass.draw_indexed(0..index_count, 0, instance_range);
+            }
+            None => render_pass.draw(0..draw_call.vertex_count, instance_range),
+        }
+    }
+
+    fn upload_model_mats(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        // SAFETY: initialized this gadget in Renderer::new()
+        let capacity = self.shared_conveyor.gadget_size(MODEL_MAT_LABEL).unwrap();
+        if capacity < data.len() as u64 {
+            self.shared_conveyor.upsert_gadget(
+                &self.device,
+                &GadgetDescriptor {
+                    label: MODEL_MAT_LABEL,
+                    index: MODEL_MAT_INDEX,
+                    size: (data.len() as u64).max(capacity * 2),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    ty: wgpu::BufferBindingType::Uniform,
+                    binding_size: wgpu::BufferSize::new(MODEL_MAT_SIZE),
+                    has_dynamic_offset: true,
+                },
+            );
+
+            // Only the buffer grows, pipelines created upon stay compatible
+            self.shared_conveyor.update_bundles(&self.device);
+        }
+
+        // SAFETY: initialized this gadget in Renderer::new()
+        self.shared_conveyor
+            .update_gadget(&self.queue, MODEL_MAT_LABEL, data)
+            .unwrap();
+    }
+
+    pub fn read_frame(&self) -> Result<FrameImage, RendererError> {
+        let texture = match &self.target {
+            RenderTarget::Texture { texture } => texture,
+            RenderTarget::Surface { .. } => return Err(RendererError::UnreadableTarget),
+        };
+
+        let swap_red_blue = match texture.format() {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            format => return Err(RendererError::UnsupportedFormat(format)),
+        };
+
+        let (width, height) = (texture.width(), texture.height());
+
+        // Rows copied out of a texture must be aligned to COPY_BYTES_PER_ROW_ALIGNMENT
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mraphics Readback Buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Mraphics Readback Encoder"),
+            });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+
+        self.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(RendererError::PollFailed)?;
+
+        // SAFETY: The device has been polled until the mapping callback ran
+        receiver.recv().unwrap().map_err(RendererError::MapFailed)?;
+
+        let mut data = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let mapped = buffer.slice(..).get_mapped_range();
+            for row in mapped.chunks(padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+
+        if swap_red_blue {
+            for pixel in data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Ok(FrameImage {
+            width,
+            height,
+            data,
+        })
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
-        self.surface_config.width = width;
-        self.surface_config.height = height;
+        self.target.resize(&self.device, width, height);
+        self.update_attachments();
+    }
+
+    // Counts from the last call to render
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn supported_sample_counts(&self) -> Vec<u32> {
+        self.multisample_flags.supported_sample_counts()
+    }
+
+    pub fn set_sample_count(&mut self, sample_count: u32) -> Result<(), RendererError> {
+        if !self.multisample_flags.sample_count_supported(sample_count) {
+            return Err(RendererError::UnsupportedSampleCount(sample_count));
+        }
+
+        self.sample_count = sample_count;
+        self.update_attachments();
+
+        Ok(())
+    }
+
+    // wgpu validates against the WebGPU guarantees unless adapter specific features are enabled
+    fn format_features(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::TextureFormatFeatures {
+        if device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            adapter.get_texture_format_features(format)
+        } else {
+            format.guaranteed_format_features(device.features())
+        }
+    }
+
+    fn update_attachments(&mut self) {
+        let (width, height) = (self.target.width(), self.target.height());
+
+        self.depth_texture =
+            Self::create_depth_texture(&self.device, width, height, self.sample_count);
+
+        self.msaa_texture = if self.sample_count > 1 {
+            Some(self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Mraphics MSAA Texture"),
+                size: wgpu::Extent3d {
+                    width: width.max(1),
+                    height: height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: self.sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.target.format(),
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            }))
+        } else {
+            None
+        };
+    }
+
+    fn create_depth_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Mraphics Depth Texture"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+    }
+}
+
+impl Renderer<'static> {
+    pub fn new_headless(width: u32, height: u32) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        pollster::block_on(async {
+            // Prefer a real GPU, but fall back to a software adapter on GPU-less machines
+            let adapter = match instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: false,
+                    compatible_surface: None,
+                    ..Default::default()
+                })
+                .await
+            {
+                Ok(adapter) => adapter,
+                Err(_) => instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        force_fallback_adapter: true,
+                        compatible_surface: None,
+                        ..Default::default()
+                    })
+                    .await
+                    .map_err(RendererError::AdapterUnavailable)?,
+            };
+
+            let (device, queue) = adapter
+                .request_device(&wgpu::DeviceDescriptor::default())
+                .await
+                .map_err(RendererError::DeviceUnavailable)?;
+
+            let target = RenderTarget::from_texture_size(
+                &device,
+                width,
+                height,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
+            );
+
+            Ok(Self::with_target(target, device, queue, &adapter))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geometry::{Cube, CubeDescriptor},
+        material::BasicMaterial,
+    };
+    use nalgebra::{Point3, Vector3};
+
+    // A flat square on the z = 0 plane, facing the default camera
+    fn quad(color: Vector3<f32>) -> Mesh {
+        let desc = CubeDescriptor {
+            width: 2.0,
+            height: 2.0,
+            depth: 0.0,
+            color,
+        };
+
+        Mesh::new(Cube::new(&desc), BasicMaterial {})
+    }
+
+    fn center_pixel(renderer: &mut Renderer, scene: &mut Scene) -> [u8; 4] {
+        renderer.render(scene).unwrap();
+        let frame = renderer.read_frame().unwrap();
+
+        let index = ((frame.height / 2 * frame.width + frame.width / 2) * 4) as usize;
+        frame.data[index..index + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn coplanar_meshes_layer_by_z_index_then_scene_order() {
+        let Ok(mut renderer) = Renderer::new_headless(16, 16) else {
+            eprintln!("skipped, no adapter available");
+            return;
+        };
+
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+        let mut scene = Scene::new();
+        scene.camera_mut().set_viewport(16.0, 16.0);
+
+        let red = scene.add_mesh(quad(Vector3::new(1.0, 0.0, 0.0)));
+        let green = scene.add_mesh(quad(Vector3::new(0.0, 1.0, 0.0)));
+        assert_eq!(center_pixel(&mut renderer, &mut scene), GREEN);
+
+        scene.bring_to_front(red);
+        assert_eq!(center_pixel(&mut renderer, &mut scene), RED);
+
+        scene.send_to_back(red);
+        assert_eq!(center_pixel(&mut renderer, &mut scene), GREEN);
+
+        scene.get_mesh_mut(red).unwrap().z_index = 1;
+        assert_eq!(center_pixel(&mut renderer, &mut scene), RED);
+
+        scene.get_mesh_mut(red).unwrap().z_index = 0;
+        scene.get_mesh_mut(green).unwrap().z_index = -1;
+        assert_eq!(center_pixel(&mut renderer, &mut scene), RED);
+    }
+
+    #[test]
+    fn z_index_overrides_depth() {
+        let Ok(mut renderer) = Renderer::new_headless(16, 16) else {
+            eprintln!("skipped, no adapter available");
+            return;
+        };
+
+        const RED: [u8; 4] = [255, 0, 0, 255];
+        const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+        let mut scene = Scene::new();
+        scene.camera_mut().set_viewport(16.0, 16.0);
+
+        // Red sits behind green but is drawn after it
+        let green = scene.add_mesh(quad(Vector3::new(0.0, 1.0, 0.0)));
+        let mut red = quad(Vector3::new(1.0, 0.0, 0.0));
+        red.set_position(&Point3::new(0.0, 0.0, -1.0));
+        let red = scene.add_mesh(red);
+        assert_eq!(center_pixel(&mut renderer, &mut scene), GREEN);
+
+        scene.get_mesh_mut(red).unwrap().z_index = 1;
+        assert_eq!(center_pixel(&mut renderer, &mut scene), RED);
 
-        self.surface.configure(&self.device, &self.surface_config);
+        // A z-indexed mesh doesn't write depth either, so later depth-tested meshes cover it
+        scene.get_mesh_mut(red).unwrap().z_index = -1;
+        scene
+            .get_mesh_mut(green)
+            .unwrap()
+            .set_position(&Point3::new(0.0, 0.0, -2.0));
+        assert_eq!(center_pixel(&mut renderer, &mut scene), GREEN);
     }
 }